@@ -0,0 +1,243 @@
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use ariadne::Cache;
+
+use crate::{
+    SrcCache,
+    output::{Region, Segment},
+    span::Span,
+    target::Target,
+};
+
+mod symbols;
+
+// the machine code, along with where each word came from in the source
+// this is used for the outputs that map the machine code back to the assembly (e.g. listings)
+pub struct Assembly {
+    // the memory map the program was assembled for
+    pub target: Target,
+
+    // the contents of the ROM, starting at the start of the ROM
+    pub machine_code: Vec<u32>,
+
+    // the initialized parts of the RAM (i.e. the data section); empty if there is no initialized
+    // data in RAM
+    pub ram: Vec<Segment>,
+
+    // where the initial values of the RAM are stored in ROM, if there is a startup directive to copy
+    // them to RAM at reset
+    pub load_image: Option<LoadImage>,
+
+    // every statement in generation order, with blocks before the statements in them
+    pub statements: Vec<AssembledStatement>,
+
+    // every label and assignment, in the order they are defined
+    pub symbols: Vec<AssembledSymbol>,
+
+    // the contents of each file, so the source can be shown without re-reading files
+    pub cache: SrcCache,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssembledStatement {
+    pub kind: StatementKind,
+    pub section: Section,
+    pub span: Span,
+
+    // the addresses of the words generated by the statement; for blocks, this is all the words in
+    // the block
+    pub addresses: Range<u32>,
+
+    // the number of blocks the statement is in, not counting the file itself
+    pub depth: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StatementKind {
+    Block,
+    Operation,
+    Label,
+    Assignment,
+    GenLiteral,
+    Export,
+    Import,
+    Org,
+    Align,
+    Section,
+    Reserve,
+    Startup,
+}
+
+// the sections statements can be placed in; each section is placed in a memory region
+// the sections in the same region share its addresses, so they are placed in the order they appear
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Section {
+    #[default]
+    Text, // code
+    Rodata, // constants
+    Data,   // initialized variables
+    Bss,    // uninitialized variables, which don't generate any words
+}
+
+impl Section {
+    pub fn in_ram(&self) -> bool {
+        match self {
+            Section::Text | Section::Rodata => false,
+            Section::Data | Section::Bss => true,
+        }
+    }
+
+    pub fn region(&self, target: &Target) -> Region {
+        if self.in_ram() {
+            target.ram
+        } else {
+            target.rom
+        }
+    }
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Text => write!(f, "text"),
+            Section::Rodata => write!(f, "rodata"),
+            Section::Data => write!(f, "data"),
+            Section::Bss => write!(f, "bss"),
+        }
+    }
+}
+
+// the initialized data in RAM, copied into the ROM at an address after all the other words
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadImage {
+    pub address: u32,
+    pub data: Range<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssembledSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+
+    // the value as it would be written in assembly, e.g. `0x0012`, `r1`, or `*(fp + 1)`
+    pub value: String,
+
+    // the number of addresses, numbers, and indirect numbers
+    pub number: Option<u32>,
+
+    // the identifier in the label or assignment
+    pub span: Span,
+
+    pub exported: bool,
+
+    // the blocks the symbol is defined in, outermost first; a block is named by the label directly
+    // before it, or by its index in the parent block (e.g. `{0}`) if there is no such label
+    pub scope: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SymbolKind {
+    Address,
+    Number,
+    Register,
+    RegisterOffset,
+    Indirect,
+    String,
+}
+
+impl Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolKind::Address => write!(f, "address"),
+            SymbolKind::Number => write!(f, "number"),
+            SymbolKind::Register => write!(f, "register"),
+            SymbolKind::RegisterOffset => write!(f, "register_offset"),
+            SymbolKind::Indirect => write!(f, "indirect"),
+            SymbolKind::String => write!(f, "string"),
+        }
+    }
+}
+
+impl Assembly {
+    // the words generated by the statement; reserved words have addresses, but no words
+    pub fn words(&self, statement: &AssembledStatement) -> &[u32] {
+        let addresses = &statement.addresses;
+        let (start, memory) = if statement.section.in_ram() {
+            match self.ram.iter().find(|segment| {
+                segment.address <= addresses.start
+                    && segment.end().is_some_and(|end| addresses.end <= end)
+            }) {
+                Some(segment) => (segment.address, segment.words.as_slice()),
+                None => return &[],
+            }
+        } else {
+            (self.target.rom.start, self.machine_code.as_slice())
+        };
+
+        let start_index = (addresses.start - start) as usize;
+        let end_index = (addresses.end - start) as usize;
+        memory.get(start_index..end_index).unwrap_or_default()
+    }
+
+    // the symbol defined by a label statement
+    pub fn label_symbol(&self, label: &AssembledStatement) -> Option<&AssembledSymbol> {
+        if label.kind != StatementKind::Label {
+            return None;
+        }
+
+        self.symbols.iter().find(|symbol| {
+            symbol.span.src == label.span.src && label.span.range().contains(&symbol.span.start())
+        })
+    }
+
+    // the name of each block statement (None for other statements), the same way blocks are named
+    // in the scope of a symbol; i.e. by the label directly before it, or by its index in the parent
+    pub fn block_names(&self) -> Vec<Option<String>> {
+        let mut names = Vec::with_capacity(self.statements.len());
+
+        // for each depth, the previous statement and the number of blocks so far in the parent
+        let mut previous: Vec<(Option<&AssembledStatement>, usize)> = Vec::new();
+
+        for (i, statement) in self.statements.iter().enumerate() {
+            // a new file, or the end of a block, starts over
+            let new_file = i == 0 || self.statements[i - 1].span.src != statement.span.src;
+            if new_file {
+                previous.clear();
+            }
+            previous.truncate(statement.depth + 1);
+            previous.resize(statement.depth + 1, (None, 0));
+
+            let (previous_statement, num_blocks) = &mut previous[statement.depth];
+
+            let name = match statement.kind {
+                StatementKind::Block => {
+                    let label = previous_statement.and_then(|label| self.label_symbol(label));
+
+                    let name = match label {
+                        Some(label) => label.name.clone(),
+                        None => format!("{{{}}}", num_blocks),
+                    };
+                    *num_blocks += 1;
+
+                    Some(name)
+                }
+                _ => None,
+            };
+
+            *previous_statement = Some(statement);
+            names.push(name);
+        }
+
+        names
+    }
+}
+
+// the 1-based line and column of the start of the span
+pub(crate) fn line_column(cache: &mut SrcCache, span: &Span) -> Option<(usize, usize)> {
+    let source = cache.fetch(&span.src).ok()?;
+    let (_, line, column) = source.get_byte_line(span.start())?;
+    Some((line + 1, column + 1))
+}
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+
+use internment::Intern;
+
+use crate::{
+    assembly::{AssembledSymbol, SymbolKind},
+    ast::{Ast, Block, Statement},
+    expression::{
+        LoopCheck,
+        expression_result::{ExpressionResult, RegisterOffset},
+    },
+    span::Spanned,
+    symbol_table::SymbolTable,
+};
+
+impl Ast {
+    // every label and assignment with its final value, in the order they are defined
+    pub(crate) fn assembled_symbols(&self) -> Vec<AssembledSymbol> {
+        let mut symbols = Vec::new();
+
+        for file in &self.files {
+            file.block.assembled_symbols(&mut Vec::new(), &mut symbols);
+        }
+
+        symbols
+    }
+}
+
+impl Block {
+    fn assembled_symbols(&self, scope: &mut Vec<String>, symbols: &mut Vec<AssembledSymbol>) {
+        let symbol_table = self.symbol_table.borrow();
+
+        // identifiers can also be exported by an export statement after they are defined
+        let exported: HashSet<_> = self
+            .statements
+            .iter()
+            .filter_map(|statement| match &statement.val {
+                Statement::Export(identifiers) => {
+                    Some(identifiers.iter().map(|identifier| identifier.val))
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        let mut num_blocks = 0;
+        let mut previous_label = None;
+
+        for statement in &self.statements {
+            match &statement.val {
+                Statement::Label(label) => {
+                    symbols.extend(assembled_symbol(
+                        &symbol_table,
+                        &label.identifier,
+                        label.export || exported.contains(&label.identifier.val),
+                        scope,
+                        true,
+                    ));
+                }
+                Statement::Assignment(assignment) => {
+                    symbols.extend(assembled_symbol(
+                        &symbol_table,
+                        &assignment.identifier,
+                        assignment.export || exported.contains(&assignment.identifier.val),
+                        scope,
+                        false,
+                    ));
+                }
+                Statement::Block(block) => {
+                    // a block directly after a label is named by the label, otherwise by its index
+                    let name = match previous_label {
+                        Some(label) => label,
+                        None => format!("{{{}}}", num_blocks),
+                    };
+                    num_blocks += 1;
+
+                    scope.push(name);
+                    block.assembled_symbols(scope, symbols);
+                    scope.pop();
+                }
+                _ => (),
+            }
+
+            previous_label = match &statement.val {
+                Statement::Label(label) => Some(label.identifier.val.to_string()),
+                _ => None,
+            };
+        }
+    }
+}
+
+fn assembled_symbol(
+    symbol_table: &SymbolTable,
+    identifier: &Spanned<Intern<String>>,
+    exported: bool,
+    scope: &[String],
+    is_label: bool,
+) -> Option<AssembledSymbol> {
+    let entry = symbol_table.get(&identifier.val)?;
+    let result = entry
+        .symbol
+        .borrow_mut()
+        .try_get_result(&mut LoopCheck::new())
+        .ok()?;
+
+    let (kind, value, number) = match (&result, is_label) {
+        (ExpressionResult::Number(Some(number)), true) => (
+            SymbolKind::Address,
+            format!("0x{:04x}", **number),
+            Some(**number),
+        ),
+        (ExpressionResult::Indirect(indirect), _) => {
+            let (value, number) = value(indirect)?;
+            (SymbolKind::Indirect, format!("*{}", value), number)
+        }
+        (result, _) => {
+            let (value, number) = value(result)?;
+            let kind = match result {
+                ExpressionResult::Number(_) => SymbolKind::Number,
+                ExpressionResult::String(_) => SymbolKind::String,
+                ExpressionResult::Register(_) => SymbolKind::Register,
+                _ => SymbolKind::RegisterOffset,
+            };
+            (kind, value, number)
+        }
+    };
+
+    Some(AssembledSymbol {
+        name: identifier.val.to_string(),
+        kind,
+        value,
+        number,
+        span: identifier.span,
+        exported,
+        scope: scope.to_vec(),
+    })
+}
+
+// the value as it would be written in assembly, and its number if it has one
+fn value(result: &ExpressionResult) -> Option<(String, Option<u32>)> {
+    Some(match result {
+        ExpressionResult::Number(Some(number)) => (format!("0x{:04x}", **number), Some(**number)),
+        ExpressionResult::String(Some(string)) => (format!("{:?}", string.0), None),
+        ExpressionResult::Register(Some(register)) => (register.to_string(), None),
+        ExpressionResult::RegisterOffset(Some(RegisterOffset { reg, offset })) => {
+            let value = match offset {
+                0 => reg.to_string(),
+                offset if *offset < 0 => format!("({} - {})", reg.val, offset.unsigned_abs()),
+                offset => format!("({} + {})", reg.val, offset),
+            };
+            (value, None)
+        }
+        _ => return None,
+    })
+}
@@ -1,11 +1,23 @@
 use internment::Intern;
 
-use crate::{span::Spanned, src::Src, symbol_table::SymbolTable};
+use crate::{
+    assembly::{LoadImage, Section},
+    span::Spanned,
+    src::Src,
+    symbol_table::SymbolTable,
+};
 use std::{cell::RefCell, rc::Rc};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Ast {
     pub(crate) files: Vec<Spanned<File>>,
+
+    // when assembling an object, only the root file (and the target module) is in the ast, and the
+    // symbols imported from other files are resolved by the linker
+    pub(crate) object: bool,
+
+    // set when calculating labels
+    pub(crate) load_image: LoadImage,
 }
 
 // this technically isn't needed, but I'm keeping it here just incase it becomes useful to
@@ -30,7 +42,12 @@ pub(crate) enum Statement {
     GenLiteral(Expression),
     Export(Vec<Spanned<Intern<String>>>),
     Import(Import),
-    Error, // if statement has invalid syntax
+    Org(Placement),
+    Align(Placement),
+    Section(Section), // the section of the following statements, until the end of the block
+    Reserve(Spanned<Expression>), // the number of words to reserve
+    Startup(Spanned<Expression>), // the entry to jump to after copying the load image to RAM
+    Error,            // if statement has invalid syntax
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +63,14 @@ pub(crate) struct Assignment {
     pub(crate) expression: Spanned<Expression>,
 }
 
+// org and align directives; the operand is the address for org and the alignment for align
+// the gap up to that address is filled with the fill word, or 0 (nop) if there is none
+#[derive(Debug, Clone)]
+pub(crate) struct Placement {
+    pub(crate) operand: Spanned<Expression>,
+    pub(crate) fill: Option<Spanned<Expression>>,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Import {
     pub(crate) src: Spanned<Intern<Src>>,
@@ -234,8 +259,7 @@ pub(crate) enum Modifier {
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum Condition {
-    #[allow(dead_code)]
-    None = 0, // not used, but for completeness
+    None = 0,
     Eq,
     Ne,
     Neg,
@@ -266,8 +290,8 @@ pub(crate) enum AluOpFlags {
     SetStatus = 1 << 0,
 }
 
-#[derive(Debug, Copy, Clone)]
-pub(crate) enum Register {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Register {
     R0 = 0,
     R1,
     R2,
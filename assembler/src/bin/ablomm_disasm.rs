@@ -0,0 +1,196 @@
+use ablomm_asm::disassembler::{self, Symbols};
+use ablomm_asm::error::RecoveredError;
+use ablomm_asm::target::Target;
+use clap::Parser;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::{
+    env, fs,
+    process::{self, ExitCode},
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// machine code file input
+    input: String,
+
+    /// symbol file input, where each line is an address followed by a name
+    #[arg(short, long)]
+    symbols: Option<String>,
+
+    /// file output
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// reassemble the disassembly and report any word that differs from the input, instead of
+    /// printing the disassembly
+    #[arg(long)]
+    verify_roundtrip: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let Some(machine_code) = read_file(&args.input) else {
+        return ExitCode::FAILURE;
+    };
+
+    let machine_code = match disassembler::parse_machine_code(&machine_code) {
+        Ok(machine_code) => machine_code,
+        Err(error) => {
+            let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let symbols = match &args.symbols {
+        Some(symbols_file) => {
+            let Some(symbols) = read_file(symbols_file) else {
+                return ExitCode::FAILURE;
+            };
+
+            match disassembler::parse_symbols(&symbols) {
+                Ok(symbols) => symbols,
+                Err(error) => {
+                    let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => Symbols::default(),
+    };
+
+    let assembly = disassembler::disassemble(&machine_code, &symbols);
+
+    if args.verify_roundtrip {
+        return verify_roundtrip(&machine_code, &assembly, args.output.as_deref());
+    }
+
+    match &args.output {
+        Some(output_file) => {
+            if !write_file(output_file, &assembly) {
+                return ExitCode::FAILURE;
+            }
+        }
+        None => {
+            print!("{}", assembly); // panics for io errors
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn verify_roundtrip(machine_code: &[u32], assembly: &str, output: Option<&str>) -> ExitCode {
+    // the assembler only reads from files, so the disassembly is written to the output file, or a
+    // temporary file if there is no output file
+    let assembly_file = match output {
+        Some(output_file) => PathBuf::from(output_file),
+        None => env::temp_dir().join(format!("ablomm_disasm_{}.asm", process::id())),
+    };
+
+    let assembly_file_string = assembly_file.to_string_lossy();
+    if !write_file(&assembly_file_string, assembly) {
+        return ExitCode::FAILURE;
+    }
+
+    // the disassembly is not checked against the memories of the target, as data words may decode
+    // as an ld of any address
+    let reassembled = ablomm_asm::assemble_unchecked(&assembly_file_string, &Target::default());
+
+    if output.is_none() {
+        let _ = fs::remove_file(&assembly_file);
+    }
+
+    let reassembled = match reassembled {
+        Ok(reassembled) => reassembled,
+        Err(RecoveredError(_, (errors, mut cache))) => {
+            for error in errors {
+                let _ = error.eprint(&mut cache);
+            }
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    print_classification(machine_code);
+    println!();
+
+    let mut num_differences = 0;
+    for address in 0..machine_code.len().max(reassembled.len()) {
+        let original = machine_code.get(address);
+        let new = reassembled.get(address);
+
+        if original != new {
+            println!(
+                "0x{:04x}: expected {}, but found {}",
+                address,
+                original.map_or("nothing".to_string(), |word| format!("{:08x}", word)),
+                new.map_or("nothing".to_string(), |word| format!("{:08x}", word)),
+            );
+            num_differences += 1;
+        }
+    }
+
+    if num_differences == 0 {
+        println!(
+            "Round trip verified: all {} words are identical",
+            machine_code.len()
+        );
+        ExitCode::SUCCESS
+    } else {
+        println!("Round trip failed: {} words differ", num_differences);
+        ExitCode::FAILURE
+    }
+}
+
+// prints each run of words with the same classification, e.g. "0x0000 to 0x002a: code (43 words)"
+fn print_classification(machine_code: &[u32]) {
+    let kinds = disassembler::classify(machine_code);
+
+    let mut start = 0;
+    for end in 1..=kinds.len() {
+        if end == kinds.len() || kinds[end] != kinds[start] {
+            println!(
+                "0x{:04x} to 0x{:04x}: {} ({} words)",
+                start,
+                end - 1,
+                kinds[start],
+                end - start
+            );
+            start = end;
+        }
+    }
+}
+
+fn write_file(file: &str, contents: &str) -> bool {
+    match fs::write(file, contents) {
+        Ok(_) => true,
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "Error while writing to file \"{}\": {}",
+                file,
+                error
+            );
+
+            false
+        }
+    }
+}
+
+fn read_file(file: &str) -> Option<String> {
+    match fs::read_to_string(file) {
+        Ok(contents) => Some(contents),
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "Error while reading file \"{}\": {}",
+                file,
+                error
+            );
+
+            None
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use ablomm_asm::emulator::{self, Campaign, Machine, Register, Tty, Vcd};
+use ablomm_asm::output::{self, DebugInfo};
+use ablomm_asm::target::Target;
+use clap::Parser;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+mod tui;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// machine code file input, in the format the simulator reads (--format hex or vmem)
+    input: String,
+
+    /// file input for the initial contents of the RAM, as written by --ram-output
+    #[arg(long)]
+    ram: Option<String>,
+
+    /// TOML file describing the ROM, RAM, and devices of the machine [default: the simulator]
+    #[arg(short, long)]
+    target: Option<String>,
+
+    /// stop with an error if the machine has not shut down after this many clock cycles
+    #[arg(long)]
+    max_cycles: Option<u64>,
+
+    /// file output for a value change dump of every clock cycle, with the signal names of the
+    /// simulator
+    #[arg(long)]
+    vcd: Option<String>,
+
+    /// TOML file describing faults to inject, and the tty output the program should still write;
+    /// a report of the faults is printed to stderr
+    #[arg(long, requires = "max_cycles")]
+    faults: Option<String>,
+
+    /// show the machine in a full-screen terminal UI, where it is stepped with the keyboard
+    #[arg(long, conflicts_with_all = ["vcd", "faults", "max_cycles"])]
+    tui: bool,
+
+    /// debug information file input, as written by --debug-info, to show the source of each
+    /// instruction in the terminal UI
+    #[arg(long, requires = "tui")]
+    debug_info: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let target = match &args.target {
+        Some(target_file) => {
+            let Some(target) =
+                read_file(target_file).and_then(|target| or_print(Target::parse(&target)))
+            else {
+                return ExitCode::FAILURE;
+            };
+            target
+        }
+        None => Target::default(),
+    };
+
+    let Some(mut machine) = or_print(Machine::with_devices(&target)) else {
+        return ExitCode::FAILURE;
+    };
+
+    let memories = [
+        (Some(&args.input), target.rom),
+        (args.ram.as_ref(), target.ram),
+    ];
+    for (file, region) in memories {
+        let Some(file) = file else {
+            continue;
+        };
+
+        let Some(segments) =
+            read_file(file).and_then(|memory| or_print(emulator::parse_memory(&memory, region)))
+        else {
+            return ExitCode::FAILURE;
+        };
+
+        for segment in &segments {
+            if or_print(machine.load(segment)).is_none() {
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if args.tui {
+        let debug_info = match &args.debug_info {
+            Some(debug_info_file) => {
+                let Some(debug_info) = read_file(debug_info_file)
+                    .and_then(|debug_info| or_print(output::parse_debug_info(&debug_info)))
+                else {
+                    return ExitCode::FAILURE;
+                };
+                debug_info
+            }
+            None => DebugInfo::default(),
+        };
+
+        return match tui::run(machine, debug_info, target.ram.start) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                let _ = writeln!(io::stderr(), "Error in the terminal UI: {}", error);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let campaign = match &args.faults {
+        Some(faults_file) => {
+            let Some(campaign) =
+                read_file(faults_file).and_then(|campaign| or_print(Campaign::parse(&campaign)))
+            else {
+                return ExitCode::FAILURE;
+            };
+            campaign
+        }
+        None => Campaign::default(),
+    };
+
+    for fault in &campaign.faults {
+        if or_print(machine.add_fault(fault.clone())).is_none() {
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let mut vcd = match &args.vcd {
+        Some(vcd_file) => {
+            let vcd =
+                File::create(vcd_file).and_then(|file| Vcd::new(BufWriter::new(file), &machine));
+            match vcd {
+                Ok(vcd) => Some(vcd),
+                Err(error) => return write_error(vcd_file, error),
+            }
+        }
+        None => None,
+    };
+
+    // the dump has every cycle, so the machine is only stepped if there is no dump
+    let max_cycles = args.max_cycles.unwrap_or(u64::MAX);
+    let mut output = Vec::new();
+    let mut reached = None; // the cycle the expected output was written
+    while !machine.is_shutdown() && machine.cycles() < max_cycles && reached.is_none() {
+        match &mut vcd {
+            Some(vcd) => {
+                if let Err(error) = vcd.cycle(&mut machine) {
+                    return write_error(args.vcd.as_deref().unwrap_or_default(), error);
+                }
+            }
+            None => machine.step(),
+        }
+
+        let new_output = print_tty(&mut machine);
+        if let Some(expected_output) = &campaign.expected_output {
+            // only the new output can complete the expected output, so the search starts at the
+            // last bytes of the old output that could be the start of it
+            let overlap = expected_output.len().saturating_sub(1);
+            let searched = output.len().saturating_sub(overlap);
+            output.extend(new_output);
+            if contains(&output[searched..], expected_output.as_bytes()) {
+                reached = Some(machine.cycles());
+            }
+        }
+    }
+
+    if let Some(vcd) = vcd
+        && let Err(error) = vcd.finish(&machine)
+    {
+        return write_error(args.vcd.as_deref().unwrap_or_default(), error);
+    }
+
+    for injection in machine.injections() {
+        let _ = writeln!(io::stderr(), "{}", injection);
+    }
+
+    if let Some(expected_output) = &campaign.expected_output {
+        return report(&machine, expected_output, reached);
+    }
+
+    if machine.is_shutdown() {
+        ExitCode::SUCCESS
+    } else {
+        let _ = writeln!(
+            io::stderr(),
+            "The machine did not shut down within {} cycles; pc is 0x{:04x}",
+            max_cycles,
+            machine.register(Register::Pc)
+        );
+        ExitCode::FAILURE
+    }
+}
+
+// the tty output is printed as it is written, so output is seen even if the program never stops
+fn print_tty(machine: &mut Machine) -> Vec<u8> {
+    let Some(tty) = machine.device_mut::<Tty>("tty") else {
+        return Vec::new();
+    };
+
+    let output = tty.take_output();
+    if !output.is_empty() {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(&output);
+        let _ = stdout.flush();
+    }
+
+    output
+}
+
+// succeeds if the expected output was written despite the faults
+fn report(machine: &Machine, expected_output: &str, reached: Option<u64>) -> ExitCode {
+    let mut stderr = io::stderr();
+    match reached {
+        Some(cycle) => {
+            let _ = writeln!(
+                stderr,
+                "The expected output {:?} was written by cycle {}, after {} injected faults",
+                expected_output,
+                cycle,
+                machine.injections().len()
+            );
+            ExitCode::SUCCESS
+        }
+        None => {
+            let _ = writeln!(
+                stderr,
+                "The expected output {:?} was not written within {} cycles, after {} injected faults; pc is 0x{:04x}",
+                expected_output,
+                machine.cycles(),
+                machine.injections().len(),
+                machine.register(Register::Pc)
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn contains(output: &[u8], expected_output: &[u8]) -> bool {
+    expected_output.is_empty()
+        || output
+            .windows(expected_output.len())
+            .any(|window| window == expected_output)
+}
+
+fn or_print<T>(result: Result<T, ablomm_asm::error::Error>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+            None
+        }
+    }
+}
+
+fn write_error(file: &str, error: io::Error) -> ExitCode {
+    let _ = writeln!(
+        io::stderr(),
+        "Error while writing to file \"{}\": {}",
+        file,
+        error
+    );
+
+    ExitCode::FAILURE
+}
+
+fn read_file(file: &str) -> Option<String> {
+    match fs::read_to_string(file) {
+        Ok(contents) => Some(contents),
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "Error while reading file \"{}\": {}",
+                file,
+                error
+            );
+
+            None
+        }
+    }
+}
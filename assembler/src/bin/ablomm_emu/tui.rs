@@ -0,0 +1,335 @@
+// a full-screen terminal front end for the emulator, which shows the machine as it is stepped
+
+use std::{io, path::Path, time::Duration};
+
+use ablomm_asm::disassembler::{self, Symbols};
+use ablomm_asm::emulator::{
+    CARRY_BIT, INTERUPT_ENABLE_BIT, Machine, NEGATIVE_BIT, OVERFLOW_BIT, Register, State, Tty,
+    USER_MODE_BIT, ZERO_BIT,
+};
+use ablomm_asm::output::DebugInfo;
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+};
+
+// the cycles run between each frame while the machine is running
+const RUN_CYCLES: u64 = 10_000;
+
+// the words in each row of the memory hexdump
+const ROW_WORDS: u32 = 4;
+
+const HELP: &str = " s: step  c: cycle  r: run/pause  ↑/↓/PgUp/PgDn: memory  q: quit ";
+
+struct App {
+    machine: Machine,
+    debug_info: DebugInfo,
+    symbols: Symbols,
+    tty: String,
+    memory_address: u32, // the first address in the hexdump
+    running: bool,
+}
+
+// shows the machine until q is pressed; the debug information maps the disassembly to the source,
+// and the hexdump starts at the memory address (e.g. the RAM, where the data of the program is)
+pub fn run(machine: Machine, mut debug_info: DebugInfo, memory_address: u32) -> io::Result<()> {
+    debug_info.read_sources();
+
+    let mut app = App {
+        memory_address,
+        symbols: debug_info.symbols(),
+        machine,
+        debug_info,
+        tty: String::new(),
+        running: false,
+    };
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // while running, the machine runs between frames until a key is pressed
+            if self.running && !event::poll(Duration::ZERO)? {
+                let end = self.machine.cycles() + RUN_CYCLES;
+                while !self.machine.is_shutdown() && self.machine.cycles() < end {
+                    self.machine.step();
+                }
+                self.take_tty();
+                self.running &= !self.machine.is_shutdown();
+                continue;
+            }
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let page = ROW_WORDS * 8;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('s') | KeyCode::Char(' ') => self.machine.step(),
+                KeyCode::Char('c') => self.machine.cycle(),
+                KeyCode::Char('r') => self.running = !self.running,
+                KeyCode::Up => self.scroll_memory(ROW_WORDS.wrapping_neg()),
+                KeyCode::Down => self.scroll_memory(ROW_WORDS),
+                KeyCode::PageUp => self.scroll_memory(page.wrapping_neg()),
+                KeyCode::PageDown => self.scroll_memory(page),
+                _ => (),
+            }
+            self.take_tty();
+        }
+    }
+
+    fn take_tty(&mut self) {
+        if let Some(tty) = self.machine.device_mut::<Tty>("tty") {
+            self.tty
+                .push_str(&String::from_utf8_lossy(&tty.take_output()));
+        }
+    }
+
+    fn scroll_memory(&mut self, words: u32) {
+        self.memory_address = self.memory_address.wrapping_add(words) & 0xffff;
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, tty, help] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(40)]).areas(main);
+        let [code, memory] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(10)]).areas(left);
+        let [registers, stack] =
+            Layout::vertical([Constraint::Length(12), Constraint::Min(0)]).areas(right);
+
+        frame.render_widget(self.code(code), code);
+        frame.render_widget(self.memory(memory), memory);
+        frame.render_widget(self.registers(), registers);
+        frame.render_widget(self.stack(stack), stack);
+        frame.render_widget(self.tty(tty), tty);
+        frame.render_widget(self.help(), help);
+    }
+
+    // the disassembly around the current instruction, with the source of each word
+    fn code(&self, area: Rect) -> Paragraph<'_> {
+        // between instructions, the next instruction is at pc
+        let current = match self.machine.state() {
+            State::Fetch => self.machine.register(Register::Pc),
+            _ => self.machine.instruction_address(),
+        } & 0xffff;
+
+        let rows = area.height.saturating_sub(2) as u32;
+        let first = current.saturating_sub(rows / 3);
+        let lines = (first..first + rows)
+            .filter(|address| *address <= 0xffff)
+            .map(|address| {
+                let Some(word) = self.machine.read_memory(address) else {
+                    return Line::from(format!("  0x{:04x}  --------", address)).dim();
+                };
+
+                let statement = disassembler::disassemble_instruction(word, &self.symbols)
+                    .unwrap_or_else(|| format!("0x{:08x}", word));
+                let line = Line::from(vec![
+                    Span::raw(if address == current { "▶ " } else { "  " }),
+                    Span::raw(format!("0x{:04x}  {:08x}  ", address, word)).dim(),
+                    Span::raw(format!("{:<32}", statement)),
+                    Span::raw(self.source(address)).dim(),
+                ]);
+
+                if address == current {
+                    line.reversed()
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines).block(Block::bordered().title(" Code "))
+    }
+
+    // e.g. "counter.asm:12  ld tty, num;"; the directory of the file is left out to save space
+    fn source(&self, address: u32) -> String {
+        let Some(word) = self.debug_info.word(address) else {
+            return String::new();
+        };
+
+        let file = self.debug_info.file(word).unwrap_or_default();
+        let file = Path::new(file)
+            .file_name()
+            .map_or(file.into(), |name| name.to_string_lossy());
+        let line = self.debug_info.line(word).unwrap_or_default();
+        format!("{}:{}  {}", file, word.location.line, line)
+    }
+
+    fn registers(&self) -> Paragraph<'_> {
+        let register = |register: Register| {
+            format!(
+                "{:<6} 0x{:08x}",
+                register.to_string(),
+                self.machine.register(register)
+            )
+        };
+
+        let general = [
+            Register::R0,
+            Register::R1,
+            Register::R2,
+            Register::R3,
+            Register::R4,
+            Register::R5,
+            Register::R6,
+            Register::R7,
+            Register::R8,
+            Register::R9,
+            Register::R10,
+            Register::Sp,
+        ];
+        let mut lines: Vec<_> = general
+            .chunks(2)
+            .map(|pair| Line::from(format!("{}  {}", register(pair[0]), register(pair[1]))))
+            .collect();
+
+        let pc_lr = format!("{}  {}", register(Register::Lr), register(Register::Pc));
+        lines.push(Line::from(pc_lr));
+
+        // set flags are bold, clear flags are dimmed
+        let status = self.machine.register(Register::Status);
+        let mut status_line = vec![Span::raw(format!("status 0x{:02x} ", status))];
+        let flags = [
+            ("N", NEGATIVE_BIT),
+            ("Z", ZERO_BIT),
+            ("C", CARRY_BIT),
+            ("V", OVERFLOW_BIT),
+            ("I", INTERUPT_ENABLE_BIT),
+        ];
+        for (name, bit) in flags {
+            status_line.push(if status & bit != 0 {
+                Span::raw(format!(" {}", name)).bold()
+            } else {
+                Span::raw(format!(" {}", name.to_lowercase())).dim()
+            });
+        }
+        status_line.push(Span::raw(if status & USER_MODE_BIT != 0 {
+            "  user"
+        } else {
+            "  supervisor"
+        }));
+        lines.push(Line::from(status_line));
+
+        lines.push(Line::from(format!(
+            "ir     0x{:08x}  state  {}",
+            self.machine.ir(),
+            self.machine.state()
+        )));
+        lines.push(Line::from(format!(
+            "cycles {}  instructions {}",
+            self.machine.cycles(),
+            self.machine.instructions()
+        )));
+
+        Paragraph::new(lines).block(Block::bordered().title(" Registers "))
+    }
+
+    // the words from sp up, with their offsets from sp and fp
+    fn stack(&self, area: Rect) -> Paragraph<'_> {
+        let sp = self.machine.register(Register::Sp) & 0xffff;
+        let fp = self.machine.register(Register::R10) & 0xffff;
+
+        let rows = area.height.saturating_sub(2) as u32;
+        let lines = (0..rows)
+            .map(|offset| {
+                let address = sp.wrapping_add(offset) & 0xffff;
+                let fp_offset = address as i64 - fp as i64;
+                let value = self
+                    .machine
+                    .read_memory(address)
+                    .map_or("--------".to_string(), |value| format!("{:08x}", value));
+
+                let line = Line::from(format!(
+                    "0x{:04x}  sp+{:<3} fp{:<+6} {}",
+                    address, offset, fp_offset, value
+                ));
+                if address == fp { line.bold() } else { line }
+            })
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines).block(Block::bordered().title(" Stack "))
+    }
+
+    // each row is the words from an address, and the characters in their lowest bytes
+    fn memory(&self, area: Rect) -> Paragraph<'_> {
+        let rows = area.height.saturating_sub(2) as u32;
+        let lines = (0..rows)
+            .map(|row| {
+                let start = self.memory_address + row * ROW_WORDS;
+                let words: Vec<_> = (start..start + ROW_WORDS)
+                    .map(|address| self.machine.read_memory(address & 0xffff))
+                    .collect();
+
+                let hex: Vec<_> = words
+                    .iter()
+                    .map(|word| word.map_or("--------".to_string(), |word| format!("{:08x}", word)))
+                    .collect();
+                let chars: String = words
+                    .iter()
+                    .map(|word| match word.map(|word| word as u8 as char) {
+                        Some(c) if c.is_ascii_graphic() || c == ' ' => c,
+                        _ => '.',
+                    })
+                    .collect();
+
+                Line::from(format!(
+                    "0x{:04x}  {}  |{}|",
+                    start & 0xffff,
+                    hex.join(" "),
+                    chars
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines).block(Block::bordered().title(" Memory "))
+    }
+
+    // the last lines written to the tty
+    fn tty(&self, area: Rect) -> Paragraph<'_> {
+        let rows = area.height.saturating_sub(2) as usize;
+        let lines: Vec<_> = self.tty.split('\n').collect();
+        let lines = lines[lines.len().saturating_sub(rows)..]
+            .iter()
+            .map(|line| Line::from(line.to_string()))
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines).block(Block::bordered().title(" TTY "))
+    }
+
+    fn help(&self) -> Line<'_> {
+        let state = if self.machine.is_shutdown() {
+            " shut down "
+        } else if self.running {
+            " running "
+        } else {
+            " paused "
+        };
+
+        Line::from(vec![
+            Span::styled(state, Style::new().reversed()),
+            Span::raw(HELP),
+        ])
+    }
+}
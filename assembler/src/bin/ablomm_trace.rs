@@ -0,0 +1,94 @@
+use ablomm_asm::output::{self, DebugInfo};
+use ablomm_asm::trace;
+use clap::Parser;
+use std::fs;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// value change dump input, from the simulator (+vcd=<FILE>) or the emulator (--vcd)
+    input: String,
+
+    /// debug information file input, as written by --debug-info, to annotate each instruction with
+    /// the file and line it came from
+    #[arg(short, long)]
+    debug_info: Option<String>,
+
+    /// file output
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let Some(vcd) = read_file(&args.input) else {
+        return ExitCode::FAILURE;
+    };
+
+    let Some(fetches) = or_print(trace::parse_fetches(&vcd)) else {
+        return ExitCode::FAILURE;
+    };
+
+    let mut debug_info = match &args.debug_info {
+        Some(debug_info_file) => {
+            let Some(debug_info) = read_file(debug_info_file)
+                .and_then(|debug_info| or_print(output::parse_debug_info(&debug_info)))
+            else {
+                return ExitCode::FAILURE;
+            };
+            debug_info
+        }
+        None => DebugInfo::default(),
+    };
+    debug_info.read_sources();
+
+    let trace = trace::annotate(&fetches, &debug_info);
+
+    match &args.output {
+        Some(output_file) => {
+            if let Err(error) = fs::write(output_file, trace) {
+                let _ = writeln!(
+                    io::stderr(),
+                    "Error while writing to file \"{}\": {}",
+                    output_file,
+                    error
+                );
+                return ExitCode::FAILURE;
+            }
+        }
+        None => {
+            print!("{}", trace); // panics for io errors
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn or_print<T>(result: Result<T, ablomm_asm::error::Error>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+            None
+        }
+    }
+}
+
+fn read_file(file: &str) -> Option<String> {
+    match fs::read_to_string(file) {
+        Ok(contents) => Some(contents),
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "Error while reading file \"{}\": {}",
+                file,
+                error
+            );
+
+            None
+        }
+    }
+}
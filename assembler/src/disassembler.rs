@@ -0,0 +1,114 @@
+use instruction::Instruction;
+use symbols::Labels;
+use word::{StringLiteral, Word};
+
+use crate::error::Error;
+
+pub(crate) mod instruction;
+mod symbols;
+mod word;
+
+pub use symbols::{Symbols, parse_symbols};
+pub use word::WordKind;
+
+// parses machine code in the format the assembler outputs (one 32-bit hex word per line)
+pub fn parse_machine_code(machine_code: &str) -> Result<Vec<u32>, Error> {
+    let mut words = Vec::new();
+
+    for (line_number, line) in machine_code.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let word = u32::from_str_radix(line, 16).map_err(|error| {
+            Error::Bare(format!(
+                "Invalid machine code word \"{}\" on line {}: {}",
+                line,
+                line_number + 1,
+                error
+            ))
+        })?;
+
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+// guesses whether each word is code, part of a string, or some other data
+pub fn classify(machine_code: &[u32]) -> Vec<WordKind> {
+    Word::classify(machine_code)
+        .iter()
+        .map(WordKind::from)
+        .collect()
+}
+
+// disassembles a single instruction (e.g. the one being executed), with addresses written as the
+// symbols at those addresses; None if the word is not an instruction
+pub fn disassemble_instruction(word: u32, symbols: &Symbols) -> Option<String> {
+    let instruction = Instruction::decode(word)?;
+    let labels = Labels::new(symbols, []);
+    Some(instruction.with_labels(&labels).to_string())
+}
+
+// returns assembly that assembles back to the given machine code
+// words that are not instructions (or are instructions the assembler cannot write) are written as
+// gen literals
+pub fn disassemble(machine_code: &[u32], symbols: &Symbols) -> String {
+    let words = Word::classify(machine_code);
+
+    // a label can be placed anywhere in the machine code, including directly after the last word
+    let end_address = machine_code.len() as u32;
+    let in_machine_code = |address: u32| address <= end_address;
+
+    let branch_targets = words
+        .iter()
+        .filter_map(|word| match word {
+            Word::Code(instruction) => instruction.branch_target(),
+            _ => None,
+        })
+        .filter(|address| in_machine_code(*address));
+
+    let labels = Labels::new(symbols, branch_targets);
+
+    let mut assembly = String::new();
+
+    // symbols that are outside the machine code (e.g. memory mapped devices) can't be labels
+    let mut has_assignments = false;
+    for (address, name) in labels.iter() {
+        if !in_machine_code(address) {
+            assembly.push_str(&format!("{} = 0x{:04x};\n", name, address));
+            has_assignments = true;
+        }
+    }
+
+    if has_assignments {
+        assembly.push('\n');
+    }
+
+    for address in 0..=end_address {
+        for name in labels.names_at(address) {
+            assembly.push_str(&format!("{}:\n", name));
+        }
+
+        let Some(word) = machine_code.get(address as usize) else {
+            break;
+        };
+
+        let statement = match &words[address as usize] {
+            Word::Code(instruction) => instruction.with_labels(&labels).to_string(),
+            Word::String(bytes) => StringLiteral(bytes).to_string(),
+            Word::Data(word) => format!("0x{:08x}", word),
+        };
+
+        assembly.push_str(&format!(
+            "    {:<32} // 0x{:04x}: {:08x}\n",
+            statement + ";",
+            address,
+            word
+        ));
+    }
+
+    assembly
+}
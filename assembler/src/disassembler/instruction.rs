@@ -0,0 +1,526 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    ast::{AluCpuMnemonic, AluModifier, AluOpFlags, Condition, CpuMnemonic, Register},
+    disassembler::symbols::Labels,
+};
+
+// a decoded instruction that can be written back as assembly which generates the exact same word
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Instruction {
+    pub(crate) condition: Condition,
+    pub(crate) alu_modifier: Option<AluModifier>,
+    pub(crate) operation: DecodedOperation,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum DecodedOperation {
+    Nop,
+    Int,
+    Ld(Register, u32),            // ld <reg>, *<address>;
+    St(u32, Register),            // ld *<address>, <reg>;
+    Ldr(Register, Register, i32), // ld <reg>, *(<reg> + <offset>);
+    Str(Register, i32, Register), // ld *(<reg> + <offset>), <reg>;
+    Ldi(Register, u32),           // ld <reg>, <immediate>;
+    Mov(Register, Register),      // ld <reg>, <reg>;
+    Push(Register),
+    Pop(Register),
+    Alu(AluCpuMnemonic, AluOperands),
+}
+
+// the operands of an alu operation in the order they are written in the assembly
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum AluOperands {
+    Reg(Register),                           // not r1;
+    RegReg(Register, Register),              // add r1, r2;
+    RegNum(Register, u32),                   // add r1, 123;
+    NumReg(u32, Register),                   // sub 123, r1;
+    RegRegReg(Register, Register, Register), // add r1, r2, r3;
+    RegRegNum(Register, Register, u32),      // add r1, r2, 123;
+    RegNumReg(Register, u32, Register),      // sub r1, 123, r2;
+}
+
+impl Instruction {
+    // returns None if the word is not an instruction, or if it is an instruction that the assembler
+    // cannot generate (e.g. a reserved bit is set)
+    pub(crate) fn decode(word: u32) -> Option<Self> {
+        let condition = decode_condition(word >> 28)?;
+        let reg_a = decode_register((word >> 16) & 0xf);
+        let reg_b = decode_register((word >> 12) & 0xf);
+
+        let operation = match (word >> 20) & 0xff {
+            code if code == CpuMnemonic::Nop as u32 && word & 0xfffff == 0 => DecodedOperation::Nop,
+            code if code == CpuMnemonic::Int as u32 && word & 0xfffff == 0 => DecodedOperation::Int,
+            code if code == CpuMnemonic::Ld as u32 => DecodedOperation::Ld(reg_a, word & 0xffff),
+            code if code == CpuMnemonic::St as u32 => DecodedOperation::St(word & 0xffff, reg_a),
+            code if code == CpuMnemonic::Ldr as u32 => {
+                DecodedOperation::Ldr(reg_a, reg_b, sign_extend_offset(word))
+            }
+            code if code == CpuMnemonic::Str as u32 => {
+                DecodedOperation::Str(reg_b, sign_extend_offset(word), reg_a)
+            }
+            code if code == CpuMnemonic::Ldi as u32 => DecodedOperation::Ldi(reg_a, word & 0xffff),
+            code if code == CpuMnemonic::Push as u32 && word & 0xffff == 0 => {
+                DecodedOperation::Push(reg_a)
+            }
+            code if code == CpuMnemonic::Pop as u32 && word & 0xffff == 0 => {
+                DecodedOperation::Pop(reg_a)
+            }
+            code if code >> 4 == 0xf => {
+                let (operation, alu_modifier) = decode_alu(word)?;
+                return Some(Self {
+                    condition,
+                    alu_modifier,
+                    operation,
+                });
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            condition,
+            alu_modifier: None,
+            operation,
+        })
+    }
+}
+
+fn decode_alu(word: u32) -> Option<(DecodedOperation, Option<AluModifier>)> {
+    let mnemonic = decode_alu_mnemonic((word >> 20) & 0xf)?;
+    let flags = (word >> 16) & 0xf;
+    let immediate = flags & AluOpFlags::Immediate as u32 != 0;
+    let reverse = flags & AluOpFlags::Reverse as u32 != 0;
+
+    let alu_modifier = match flags & (AluOpFlags::Loadn as u32 | AluOpFlags::SetStatus as u32) {
+        0 => None,
+        flag if flag == AluOpFlags::SetStatus as u32 => Some(AluModifier::S),
+        flag if flag == AluOpFlags::Loadn as u32 | AluOpFlags::SetStatus as u32 => {
+            Some(AluModifier::T)
+        }
+        // loadn without set status does nothing, so the assembler has no way to write it
+        _ => return None,
+    };
+
+    let reg_a = decode_register((word >> 12) & 0xf);
+    let reg_b = decode_register((word >> 8) & 0xf);
+    let reg_c = decode_register((word >> 4) & 0xf);
+    let number = word & 0xff;
+
+    // the lowest 4 bits are unused when register C is used
+    if !immediate && (reverse || word & 0xf != 0) {
+        return None;
+    }
+
+    let operation = match mnemonic {
+        // ld <reg>, <reg>; is the only way to generate a pass, and it always leaves register B as 0
+        AluCpuMnemonic::Pass => {
+            if immediate || reverse || reg_b as u32 != 0 {
+                return None;
+            }
+            DecodedOperation::Mov(reg_a, reg_c)
+        }
+
+        // unary operations always set register B to register A
+        AluCpuMnemonic::Not | AluCpuMnemonic::Neg => {
+            if reverse || reg_a as u32 != reg_b as u32 {
+                return None;
+            }
+
+            let operands = if immediate {
+                AluOperands::RegNum(reg_a, number)
+            } else if reg_a as u32 == reg_c as u32 {
+                AluOperands::Reg(reg_a)
+            } else {
+                AluOperands::RegReg(reg_a, reg_c)
+            };
+            DecodedOperation::Alu(mnemonic, operands)
+        }
+
+        _ => {
+            let two_operands = reg_a as u32 == reg_b as u32;
+            let operands = match (immediate, reverse, two_operands) {
+                (false, _, true) => AluOperands::RegReg(reg_a, reg_c),
+                (false, _, false) => AluOperands::RegRegReg(reg_a, reg_b, reg_c),
+                (true, false, true) => AluOperands::RegNum(reg_a, number),
+                (true, false, false) => AluOperands::RegRegNum(reg_a, reg_b, number),
+                (true, true, true) => AluOperands::NumReg(number, reg_a),
+                (true, true, false) => AluOperands::RegNumReg(reg_a, number, reg_b),
+            };
+            DecodedOperation::Alu(mnemonic, operands)
+        }
+    };
+
+    Some((operation, alu_modifier))
+}
+
+// the offset of LDR and STR is a signed 12 bit value
+fn sign_extend_offset(word: u32) -> i32 {
+    ((word << 20) as i32) >> 20
+}
+
+pub(crate) fn decode_register(code: u32) -> Register {
+    match code & 0xf {
+        0x0 => Register::R0,
+        0x1 => Register::R1,
+        0x2 => Register::R2,
+        0x3 => Register::R3,
+        0x4 => Register::R4,
+        0x5 => Register::R5,
+        0x6 => Register::R6,
+        0x7 => Register::R7,
+        0x8 => Register::R8,
+        0x9 => Register::R9,
+        0xa => Register::R10,
+        0xb => Register::Status,
+        0xc => Register::Sp,
+        0xd => Register::Lr,
+        0xe => Register::Pclink,
+        _ => Register::Pc,
+    }
+}
+
+fn decode_condition(code: u32) -> Option<Condition> {
+    Some(match code {
+        0x0 => Condition::None,
+        0x1 => Condition::Eq,
+        0x2 => Condition::Ne,
+        0x3 => Condition::Neg,
+        0x4 => Condition::Pos,
+        0x5 => Condition::Vs,
+        0x6 => Condition::Vc,
+        0x7 => Condition::Ult,
+        0x8 => Condition::Ugt,
+        0x9 => Condition::Ule,
+        0xa => Condition::Uge,
+        0xb => Condition::Slt,
+        0xc => Condition::Sgt,
+        0xd => Condition::Sle,
+        0xe => Condition::Sge,
+        _ => return None,
+    })
+}
+
+fn decode_alu_mnemonic(code: u32) -> Option<AluCpuMnemonic> {
+    Some(match code {
+        0x0 => AluCpuMnemonic::Pass,
+        0x1 => AluCpuMnemonic::And,
+        0x2 => AluCpuMnemonic::Or,
+        0x3 => AluCpuMnemonic::Xor,
+        0x4 => AluCpuMnemonic::Not,
+        0x5 => AluCpuMnemonic::Add,
+        0x6 => AluCpuMnemonic::Sub,
+        0x7 => AluCpuMnemonic::Neg,
+        0x8 => AluCpuMnemonic::Shl,
+        0x9 => AluCpuMnemonic::Shr,
+        0xa => AluCpuMnemonic::Ashr,
+        0xb => AluCpuMnemonic::Rol,
+        0xc => AluCpuMnemonic::Ror,
+        _ => return None,
+    })
+}
+
+impl Instruction {
+    // the address operands of `ld pc, <address>;` and LD/ST are written as labels if there is a
+    // label at that address
+    pub(crate) fn with_labels<'a>(&'a self, labels: &'a Labels) -> LabelledInstruction<'a> {
+        LabelledInstruction {
+            instruction: self,
+            labels,
+        }
+    }
+
+    // returns the address this instruction may branch to, if it is known statically
+    pub(crate) fn branch_target(&self) -> Option<u32> {
+        match self.operation {
+            DecodedOperation::Ldi(Register::Pc | Register::Pclink, address) => Some(address),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct LabelledInstruction<'a> {
+    instruction: &'a Instruction,
+    labels: &'a Labels,
+}
+
+impl Display for LabelledInstruction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instruction = self.instruction;
+        let address = |address| Address(address, self.labels.get(address));
+
+        write!(f, "{}", instruction.operation.mnemonic())?;
+
+        if !matches!(instruction.condition, Condition::None) {
+            write!(f, ".{}", instruction.condition)?;
+        }
+
+        if let Some(alu_modifier) = instruction.alu_modifier {
+            write!(f, ".{}", alu_modifier)?;
+        }
+
+        match instruction.operation {
+            DecodedOperation::Nop | DecodedOperation::Int => Ok(()),
+            DecodedOperation::Ld(register, number) => {
+                write!(f, " {}, *{}", register, address(number))
+            }
+            DecodedOperation::St(number, register) => {
+                write!(f, " *{}, {}", address(number), register)
+            }
+            DecodedOperation::Ldr(register1, register2, offset) => {
+                write!(f, " {}, *{}", register1, RegOffset(register2, offset))
+            }
+            DecodedOperation::Str(register1, offset, register2) => {
+                write!(f, " *{}, {}", RegOffset(register1, offset), register2)
+            }
+            DecodedOperation::Ldi(register, number) => match instruction.branch_target() {
+                Some(_) => write!(f, " {}, {}", register, address(number)),
+                None => write!(f, " {}, {}", register, Address(number, None)),
+            },
+            DecodedOperation::Mov(register1, register2) => {
+                write!(f, " {}, {}", register1, register2)
+            }
+            DecodedOperation::Push(register) | DecodedOperation::Pop(register) => {
+                write!(f, " {}", register)
+            }
+            DecodedOperation::Alu(_, operands) => write!(f, " {}", operands),
+        }
+    }
+}
+
+impl DecodedOperation {
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            DecodedOperation::Nop => "nop",
+            DecodedOperation::Int => "int",
+            DecodedOperation::Ld(..)
+            | DecodedOperation::St(..)
+            | DecodedOperation::Ldr(..)
+            | DecodedOperation::Str(..)
+            | DecodedOperation::Ldi(..)
+            | DecodedOperation::Mov(..) => "ld",
+            DecodedOperation::Push(_) => "push",
+            DecodedOperation::Pop(_) => "pop",
+            DecodedOperation::Alu(mnemonic, _) => match mnemonic {
+                AluCpuMnemonic::Pass => "ld",
+                AluCpuMnemonic::And => "and",
+                AluCpuMnemonic::Or => "or",
+                AluCpuMnemonic::Xor => "xor",
+                AluCpuMnemonic::Not => "not",
+                AluCpuMnemonic::Add => "add",
+                AluCpuMnemonic::Sub => "sub",
+                AluCpuMnemonic::Neg => "neg",
+                AluCpuMnemonic::Shl => "shl",
+                AluCpuMnemonic::Shr => "shr",
+                AluCpuMnemonic::Ashr => "ashr",
+                AluCpuMnemonic::Rol => "rol",
+                AluCpuMnemonic::Ror => "ror",
+            },
+        }
+    }
+}
+
+impl Display for AluOperands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AluOperands::Reg(register) => write!(f, "{}", register),
+            AluOperands::RegReg(register1, register2) => {
+                write!(f, "{}, {}", register1, register2)
+            }
+            AluOperands::RegNum(register, number) => write!(f, "{}, {}", register, number),
+            AluOperands::NumReg(number, register) => write!(f, "{}, {}", number, register),
+            AluOperands::RegRegReg(register1, register2, register3) => {
+                write!(f, "{}, {}, {}", register1, register2, register3)
+            }
+            AluOperands::RegRegNum(register1, register2, number) => {
+                write!(f, "{}, {}, {}", register1, register2, number)
+            }
+            AluOperands::RegNumReg(register1, number, register2) => {
+                write!(f, "{}, {}, {}", register1, number, register2)
+            }
+        }
+    }
+}
+
+// the fields of a word as the CPU sees them, e.g. `cond=eq op=LDI a=pc immediate=0x0012`
+// unlike Instruction, this can show any word, including ones the assembler cannot write
+pub(crate) struct Fields(pub(crate) u32);
+
+impl Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let word = self.0;
+        let reg_a = decode_register(word >> 16);
+        let reg_b = decode_register(word >> 12);
+
+        match decode_condition(word >> 28) {
+            Some(Condition::None) => write!(f, "cond=none")?,
+            Some(condition) => write!(f, "cond={}", condition)?,
+            None => write!(f, "cond=0x{:x}", word >> 28)?,
+        }
+
+        let code = (word >> 20) & 0xff;
+        if code >> 4 == 0xf {
+            return write_alu_fields(f, word);
+        }
+
+        let op = match code {
+            code if code == CpuMnemonic::Nop as u32 => "NOP",
+            code if code == CpuMnemonic::Ld as u32 => "LD",
+            code if code == CpuMnemonic::Ldr as u32 => "LDR",
+            code if code == CpuMnemonic::Ldi as u32 => "LDI",
+            code if code == CpuMnemonic::St as u32 => "ST",
+            code if code == CpuMnemonic::Str as u32 => "STR",
+            code if code == CpuMnemonic::Push as u32 => "PUSH",
+            code if code == CpuMnemonic::Pop as u32 => "POP",
+            code if code == CpuMnemonic::Int as u32 => "INT",
+            code => return write!(f, " op=0x{:02x}", code),
+        };
+        write!(f, " op={}", op)?;
+
+        match op {
+            "LD" | "ST" => write!(f, " a={} address=0x{:04x}", reg_a, word & 0xffff),
+            "LDR" | "STR" => write!(
+                f,
+                " a={} b={} offset={}",
+                reg_a,
+                reg_b,
+                sign_extend_offset(word)
+            ),
+            "LDI" => write!(f, " a={} immediate=0x{:04x}", reg_a, word & 0xffff),
+            "PUSH" | "POP" => write!(f, " a={}", reg_a),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn write_alu_fields(f: &mut fmt::Formatter<'_>, word: u32) -> fmt::Result {
+    let op = match decode_alu_mnemonic((word >> 20) & 0xf) {
+        Some(AluCpuMnemonic::Pass) => "PASS",
+        Some(AluCpuMnemonic::And) => "AND",
+        Some(AluCpuMnemonic::Or) => "OR",
+        Some(AluCpuMnemonic::Xor) => "XOR",
+        Some(AluCpuMnemonic::Not) => "NOT",
+        Some(AluCpuMnemonic::Add) => "ADD",
+        Some(AluCpuMnemonic::Sub) => "SUB",
+        Some(AluCpuMnemonic::Neg) => "NEG",
+        Some(AluCpuMnemonic::Shl) => "SHL",
+        Some(AluCpuMnemonic::Shr) => "SHR",
+        Some(AluCpuMnemonic::Ashr) => "ASHR",
+        Some(AluCpuMnemonic::Rol) => "ROL",
+        Some(AluCpuMnemonic::Ror) => "ROR",
+        None => return write!(f, " op=0x{:02x}", (word >> 20) & 0xff),
+    };
+    write!(f, " op={}", op)?;
+
+    let flags = (word >> 16) & 0xf;
+    let flag_names: Vec<_> = [
+        (AluOpFlags::Immediate, "I"),
+        (AluOpFlags::Reverse, "R"),
+        (AluOpFlags::Loadn, "Ln"),
+        (AluOpFlags::SetStatus, "S"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags & *flag as u32 != 0)
+    .map(|(_, name)| name)
+    .collect();
+
+    if flag_names.is_empty() {
+        write!(f, " flags=-")?;
+    } else {
+        write!(f, " flags={}", flag_names.join(","))?;
+    }
+
+    write!(
+        f,
+        " a={} b={}",
+        decode_register(word >> 12),
+        decode_register(word >> 8)
+    )?;
+
+    if flags & AluOpFlags::Immediate as u32 != 0 {
+        write!(f, " immediate={}", word & 0xff)
+    } else {
+        write!(f, " c={}", decode_register(word >> 4))
+    }
+}
+
+// 16 bit addresses and immediates are written as 4 digit hex numbers, unless there is a label
+struct Address<'a>(u32, Option<&'a str>);
+
+impl Display for Address<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(label) => write!(f, "{}", label),
+            None => write!(f, "0x{:04x}", self.0),
+        }
+    }
+}
+
+struct RegOffset(Register, i32);
+
+impl Display for RegOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            0 => write!(f, "{}", self.0),
+            offset if offset < 0 => write!(f, "({} - {})", self.0, offset.unsigned_abs()),
+            offset => write!(f, "({} + {})", self.0, offset),
+        }
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Register::R0 => "r0",
+            Register::R1 => "r1",
+            Register::R2 => "r2",
+            Register::R3 => "r3",
+            Register::R4 => "r4",
+            Register::R5 => "r5",
+            Register::R6 => "r6",
+            Register::R7 => "r7",
+            Register::R8 => "r8",
+            Register::R9 => "r9",
+            Register::R10 => "fp", // r10 is conventionally used as the frame pointer
+            Register::Status => "status",
+            Register::Sp => "sp",
+            Register::Lr => "lr",
+            Register::Pclink => "pc.link",
+            Register::Pc => "pc",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Condition::None => "",
+            Condition::Eq => "eq",
+            Condition::Ne => "ne",
+            Condition::Neg => "neg",
+            Condition::Pos => "pos",
+            Condition::Vs => "vs",
+            Condition::Vc => "vc",
+            Condition::Ult => "ult",
+            Condition::Ugt => "ugt",
+            Condition::Ule => "ule",
+            Condition::Uge => "uge",
+            Condition::Slt => "slt",
+            Condition::Sgt => "sgt",
+            Condition::Sle => "sle",
+            Condition::Sge => "sge",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+impl Display for AluModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AluModifier::S => write!(f, "s"),
+            AluModifier::T => write!(f, "t"),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{error::Error, output::MAP_HEADER, utils::is_identifier};
+
+// registers can be used as identifiers in an assignment, but an identifier with the same name as a
+// register will always be parsed as the register in an operand, so they can never be used as labels
+const RESERVED: [&str; 16] = [
+    "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "fp", "status", "sp", "lr",
+    "pc",
+];
+
+// the kinds of symbols in a map file that are addresses in memory
+const MAP_ADDRESS_KINDS: [&str; 2] = ["address", "indirect"];
+
+// the addresses of identifiers given to the disassembler
+#[derive(Debug, Default, Clone)]
+pub struct Symbols(Vec<(u32, String)>);
+
+// parses a symbol file, where each line is an address followed by the name of the identifier at that
+// address, e.g. `0x0012 print`; anything after the name is ignored, as are empty lines and lines
+// starting with "//"
+// the map file written by the assembler (which starts with its header line) is also a symbol file,
+// in which case only the addresses and indirect numbers are used (i.e. the kind column is "address"
+// or "indirect")
+pub fn parse_symbols(symbols: &str) -> Result<Symbols, Error> {
+    let mut parsed = Vec::new();
+    let is_map = symbols.trim_start().starts_with(MAP_HEADER);
+
+    for (line_number, line) in symbols.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        let mut columns = line.split_whitespace();
+        let (Some(address), Some(name)) = (columns.next(), columns.next()) else {
+            return Err(Error::Bare(format!(
+                "Invalid symbol on line {}: expected an address followed by a name",
+                line_number + 1
+            )));
+        };
+
+        // symbols in a map file that are not addresses (e.g. numbers, or registers which have no
+        // number at all)
+        if is_map
+            && (address == "-"
+                || columns
+                    .next()
+                    .is_some_and(|kind| !MAP_ADDRESS_KINDS.contains(&kind)))
+        {
+            continue;
+        }
+
+        let address =
+            u32::from_str_radix(address.trim_start_matches("0x"), 16).map_err(|error| {
+                Error::Bare(format!(
+                    "Invalid symbol address \"{}\" on line {}: {}",
+                    address,
+                    line_number + 1,
+                    error
+                ))
+            })?;
+
+        if !is_identifier(name) {
+            return Err(Error::Bare(format!(
+                "Invalid symbol name \"{}\" on line {}",
+                name,
+                line_number + 1
+            )));
+        }
+
+        if !RESERVED.contains(&name) {
+            parsed.push((address, name.to_string()));
+        }
+    }
+
+    Ok(Symbols(parsed))
+}
+
+impl Symbols {
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.0
+            .iter()
+            .map(|(address, name)| (*address, name.as_str()))
+    }
+}
+
+// symbols from elsewhere, e.g. the debug information; registers are ignored as they are when parsed
+impl FromIterator<(u32, String)> for Symbols {
+    fn from_iter<T: IntoIterator<Item = (u32, String)>>(symbols: T) -> Self {
+        Symbols(
+            symbols
+                .into_iter()
+                .filter(|(_, name)| !RESERVED.contains(&name.as_str()))
+                .collect(),
+        )
+    }
+}
+
+// the final names used in the disassembly, with each name being unique
+#[derive(Debug, Default)]
+pub(crate) struct Labels(BTreeMap<u32, Vec<String>>);
+
+impl Labels {
+    // branch targets without a symbol get a generated label; i.e. L_<address>
+    pub(crate) fn new(symbols: &Symbols, branch_targets: impl IntoIterator<Item = u32>) -> Self {
+        let mut labels = Labels::default();
+        let mut used_names = HashSet::new();
+
+        let mut symbols = symbols.0.clone();
+        symbols.sort_by_key(|(address, _)| *address);
+
+        for (address, name) in symbols {
+            // the same name may be defined in different scopes, but the disassembly only has one
+            let name = if used_names.contains(&name) {
+                format!("{}_{:04x}", name, address)
+            } else {
+                name
+            };
+
+            if used_names.insert(name.clone()) {
+                labels.0.entry(address).or_default().push(name);
+            }
+        }
+
+        for address in branch_targets {
+            if !labels.0.contains_key(&address) {
+                let name = format!("L_{:04x}", address);
+                if used_names.insert(name.clone()) {
+                    labels.0.entry(address).or_default().push(name);
+                }
+            }
+        }
+
+        labels
+    }
+
+    // the name to use when the address is an operand
+    pub(crate) fn get(&self, address: u32) -> Option<&str> {
+        self.0
+            .get(&address)
+            .and_then(|names| names.first())
+            .map(String::as_str)
+    }
+
+    pub(crate) fn names_at(&self, address: u32) -> &[String] {
+        self.0.get(&address).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u32, &String)> {
+        self.0
+            .iter()
+            .flat_map(|(address, names)| names.iter().map(|name| (*address, name)))
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::{self, Display};
+
+use crate::disassembler::instruction::Instruction;
+
+// a guess of what a word in the machine code is used for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WordKind {
+    Code,
+    String,
+    Data,
+}
+
+impl Display for WordKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordKind::Code => write!(f, "code"),
+            WordKind::String => write!(f, "string"),
+            WordKind::Data => write!(f, "data"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum Word {
+    Code(Instruction),
+    String([u8; 4]), // 4 characters packed in a word, the same way a string gen literal is generated
+    Data(u32),
+}
+
+impl From<&Word> for WordKind {
+    fn from(value: &Word) -> Self {
+        match value {
+            Word::Code(_) => WordKind::Code,
+            Word::String(_) => WordKind::String,
+            Word::Data(_) => WordKind::Data,
+        }
+    }
+}
+
+impl Word {
+    // strings are recognized by each word being made of printable characters, possibly padded with
+    // null characters. A word that is also a valid instruction is only treated as a string if it
+    // continues a string from the previous word (i.e. the previous word had no null character)
+    pub(crate) fn classify(machine_code: &[u32]) -> Vec<Self> {
+        let mut words = Vec::with_capacity(machine_code.len());
+        let mut in_string = false;
+
+        for word in machine_code {
+            let bytes = word.to_be_bytes();
+            let instruction = Instruction::decode(*word);
+
+            let classified = if is_string(&bytes) && (in_string || instruction.is_none()) {
+                Word::String(bytes)
+            } else if let Some(instruction) = instruction {
+                Word::Code(instruction)
+            } else {
+                Word::Data(*word)
+            };
+
+            in_string = matches!(classified, Word::String(bytes) if !bytes.contains(&0));
+            words.push(classified);
+        }
+
+        words
+    }
+}
+
+fn is_string(bytes: &[u8; 4]) -> bool {
+    // null characters are only valid as padding at the end of the word
+    let length = bytes.iter().position(|byte| *byte == 0).unwrap_or(4);
+
+    bytes[..length]
+        .iter()
+        .all(|byte| byte.is_ascii_graphic() || b" \t\n\r".contains(byte))
+        && bytes[length..].iter().all(|byte| *byte == 0)
+}
+
+// writes the word as a string literal that generates the exact same word
+pub(crate) struct StringLiteral<'a>(pub(crate) &'a [u8; 4]);
+
+impl Display for StringLiteral<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for byte in self.0 {
+            match byte {
+                b'\0' => write!(f, "\\0")?,
+                b'\t' => write!(f, "\\t")?,
+                b'\n' => write!(f, "\\n")?,
+                b'\r' => write!(f, "\\r")?,
+                b'\\' => write!(f, "\\\\")?,
+                b'"' => write!(f, "\\\"")?,
+                byte => write!(f, "{}", *byte as char)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
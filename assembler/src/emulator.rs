@@ -0,0 +1,730 @@
+// an emulator of the CPU (see cpu/hdl) and the devices of the simulator (see cpu/simulation)
+// the emulator is cycle accurate: each cycle is one clock cycle of cpu.sv, with the same control
+// unit states and the same values on the buses, so it can be compared directly with the simulator
+
+use std::any::Any;
+
+use cpu::{Control, Cpu};
+
+use crate::{
+    error::Error,
+    output::{Region, Segment},
+    target::Target,
+};
+
+mod alu;
+mod cpu;
+mod device;
+mod devices;
+mod faults;
+mod vcd;
+
+pub use crate::ast::Register;
+pub use cpu::State;
+pub use device::{Device, Power};
+pub use devices::{InterruptController, PowerController, Timer, Tty};
+pub use faults::{Campaign, Fault, Injection};
+pub use vcd::Vcd;
+
+// the status register bits (see docs/cpu/isa.md)
+pub const NEGATIVE_BIT: u32 = 1 << 5;
+pub const ZERO_BIT: u32 = 1 << 4;
+pub const CARRY_BIT: u32 = 1 << 3;
+pub const OVERFLOW_BIT: u32 = 1 << 2;
+pub const INTERUPT_ENABLE_BIT: u32 = cpu::IMASK;
+pub const USER_MODE_BIT: u32 = cpu::USER_MODE;
+
+// the signals between the CPU and the memory and devices in a cycle (the ports of cpu.sv)
+// buses that are not driven are 0
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Bus {
+    pub addr: u32,
+    pub data: u32,
+    pub rd: bool,
+    pub wr: bool,
+}
+
+// an instruction that was decoded; executed is false if its condition was not satisfied
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Execute {
+    pub cycle: u64,
+    pub address: u32,
+    pub word: u32,
+    pub executed: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessKind {
+    Fetch, // a read of an instruction
+    Read,
+    Write,
+}
+
+// a read or write on the bus; the value can be changed by the callback (e.g. to corrupt a read)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Access {
+    pub cycle: u64,
+    pub kind: AccessKind,
+    pub address: u32,
+    pub value: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InterruptKind {
+    Hardware,
+    Software,  // the int instruction
+    Exception, // an invalid instruction
+}
+
+// an interrupt that is starting, i.e. the return address is about to be pushed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Interrupt {
+    pub cycle: u64,
+    pub kind: InterruptKind,
+    pub return_address: u32,
+}
+
+// why `run_until` stopped
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stop {
+    Condition,
+    Shutdown,
+}
+
+struct MappedDevice {
+    name: String,
+    region: Region,
+    irq_line: Option<u32>,
+    device: Box<dyn Device>,
+}
+
+type Callback<T> = Box<dyn FnMut(&T)>;
+type MutCallback<T> = Box<dyn FnMut(&mut T)>;
+
+#[derive(Default)]
+struct Hooks {
+    execute: Vec<Callback<Execute>>,
+    memory_access: Vec<MutCallback<Access>>,
+    interrupt: Vec<Callback<Interrupt>>,
+}
+
+pub struct Machine {
+    cpu: Cpu,
+    rom_region: Region,
+    rom: Vec<u32>,
+    ram_region: Region,
+    ram: Vec<u32>,
+    devices: Vec<MappedDevice>,
+    hooks: Hooks,
+    faults: Vec<Fault>,
+    injections: Vec<Injection>,
+    bus: Bus,
+    cycles: u64,
+    instructions: u64,
+    instruction_address: u32, // the address of the instruction in ir
+    shutdown: bool,
+}
+
+impl Machine {
+    // a machine with the ROM and RAM of the target, but none of its devices
+    pub fn new(target: &Target) -> Self {
+        Self {
+            cpu: Cpu::default(),
+            rom_region: target.rom,
+            rom: vec![0; target.rom.depth as usize],
+            ram_region: target.ram,
+            ram: vec![0; target.ram.depth as usize],
+            devices: Vec::new(),
+            hooks: Hooks::default(),
+            faults: Vec::new(),
+            injections: Vec::new(),
+            bus: Bus::default(),
+            cycles: 0,
+            instructions: 0,
+            instruction_address: 0,
+            shutdown: false,
+        }
+    }
+
+    // a machine with the ROM, RAM, and devices of the target; each device of the target must be one
+    // of the devices of the simulator (timer, ic, power, or tty), otherwise it has to be added with
+    // `add_device` to a machine from `new`
+    pub fn with_devices(target: &Target) -> Result<Self, Error> {
+        let mut machine = Self::new(target);
+        let mut irq_lines = 0..;
+
+        for device in &target.devices {
+            let region = device.region();
+            match device.name.as_str() {
+                "timer" => machine.add_device("timer", region, irq_lines.next(), Timer::default()),
+                "ic" => machine.add_device("ic", region, None, InterruptController::default()),
+                "power" => machine.add_device("power", region, None, PowerController::default()),
+                "tty" => machine.add_device("tty", region, None, Tty::default()),
+                _ => Err(Error::Bare(format!(
+                    "The device \"{}\" in the target \"{}\" can not be emulated",
+                    device.name, target.name
+                ))),
+            }?;
+        }
+
+        Ok(machine)
+    }
+
+    // maps the device to the region; the irq line is the bit of the device in the interrupt
+    // controller
+    pub fn add_device(
+        &mut self,
+        name: &str,
+        region: Region,
+        irq_line: Option<u32>,
+        device: impl Device,
+    ) -> Result<(), Error> {
+        if let Some(irq_line) = irq_line
+            && irq_line >= u16::BITS
+        {
+            return Err(Error::Bare(format!(
+                "The device \"{}\" is on irq line {}, but there are only {} irq lines",
+                name,
+                irq_line,
+                u16::BITS
+            )));
+        }
+
+        if self.devices.iter().any(|device| device.name == name) {
+            return Err(Error::Bare(format!(
+                "The device \"{}\" is added more than once",
+                name
+            )));
+        }
+
+        let regions = [("ROM", self.rom_region), ("RAM", self.ram_region)]
+            .into_iter()
+            .chain(
+                self.devices
+                    .iter()
+                    .map(|device| (device.name.as_str(), device.region)),
+            );
+        for (other_name, other) in regions {
+            if other.overlaps(&region) {
+                return Err(Error::Bare(format!(
+                    "The device \"{}\" ({}) overlaps {} ({})",
+                    name, region, other_name, other
+                )));
+            }
+        }
+
+        self.devices.push(MappedDevice {
+            name: name.to_string(),
+            region,
+            irq_line,
+            device: Box::new(device),
+        });
+
+        Ok(())
+    }
+
+    pub fn device<T: Device>(&self, name: &str) -> Option<&T> {
+        let device = self.devices.iter().find(|device| device.name == name)?;
+        (device.device.as_ref() as &dyn Any).downcast_ref()
+    }
+
+    pub fn device_mut<T: Device>(&mut self, name: &str) -> Option<&mut T> {
+        let device = self.devices.iter_mut().find(|device| device.name == name)?;
+        (device.device.as_mut() as &mut dyn Any).downcast_mut()
+    }
+
+    // the name, region, and irq line of each device
+    pub fn devices(&self) -> impl Iterator<Item = (&str, Region, Option<u32>)> {
+        self.devices
+            .iter()
+            .map(|device| (device.name.as_str(), device.region, device.irq_line))
+    }
+
+    // places the words in ROM or RAM, e.g. the machine code and RAM of an assembly
+    pub fn load(&mut self, segment: &Segment) -> Result<(), Error> {
+        let (region, memory) = if self.rom_region.contains(segment) {
+            (self.rom_region, &mut self.rom)
+        } else if self.ram_region.contains(segment) {
+            (self.ram_region, &mut self.ram)
+        } else {
+            return Err(Error::Bare(format!(
+                "Words at {} are not entirely in ROM ({}) or RAM ({})",
+                segment, self.rom_region, self.ram_region
+            )));
+        };
+
+        let start = (segment.address - region.start) as usize;
+        memory[start..start + segment.words.len()].copy_from_slice(&segment.words);
+        Ok(())
+    }
+
+    // called when each instruction is decoded, before it is executed
+    pub fn on_execute(&mut self, callback: impl FnMut(&Execute) + 'static) {
+        self.hooks.execute.push(Box::new(callback));
+    }
+
+    // called for every read and write on the bus, before the value is used
+    pub fn on_memory_access(&mut self, callback: impl FnMut(&mut Access) + 'static) {
+        self.hooks.memory_access.push(Box::new(callback));
+    }
+
+    // called when a hardware interrupt, software interrupt, or exception starts
+    pub fn on_interrupt(&mut self, callback: impl FnMut(&Interrupt) + 'static) {
+        self.hooks.interrupt.push(Box::new(callback));
+    }
+
+    // injects the fault while the machine runs; the fault is numbered by the order it was added
+    pub fn add_fault(&mut self, fault: Fault) -> Result<(), Error> {
+        if let Fault::FlipRegister { bit, .. } | Fault::FlipMemory { bit, .. } = fault
+            && bit >= u32::BITS
+        {
+            return Err(Error::Bare(format!(
+                "Can not flip bit {} of a {} bit word",
+                bit,
+                u32::BITS
+            )));
+        }
+
+        if let Fault::FlipMemory { address, .. } = fault
+            && index_of(self.rom_region, address & 0xffff).is_none()
+            && index_of(self.ram_region, address & 0xffff).is_none()
+        {
+            return Err(Error::Bare(format!(
+                "Can not flip a bit at 0x{:04x}, which is not in ROM ({}) or RAM ({})",
+                address, self.rom_region, self.ram_region
+            )));
+        }
+
+        self.faults.push(fault);
+        Ok(())
+    }
+
+    // the changes made by the faults so far
+    pub fn injections(&self) -> &[Injection] {
+        &self.injections
+    }
+
+    pub fn register(&self, register: Register) -> u32 {
+        self.cpu.register(register)
+    }
+
+    // setting pc.link sets pc, without setting lr
+    pub fn set_register(&mut self, register: Register, value: u32) {
+        self.cpu.set_register(register, value);
+    }
+
+    // the instruction being executed
+    pub fn ir(&self) -> u32 {
+        self.cpu.ir
+    }
+
+    // the address of the instruction being executed
+    pub fn instruction_address(&self) -> u32 {
+        self.instruction_address
+    }
+
+    pub fn state(&self) -> State {
+        self.cpu.state
+    }
+
+    // the signals on the bus in the last cycle
+    pub fn bus(&self) -> Bus {
+        self.bus
+    }
+
+    // the irq line of each device, as read from the interrupt controller
+    pub fn irq_lines(&self) -> u16 {
+        self.devices
+            .iter()
+            .filter(|device| device.device.irq())
+            .filter_map(|device| device.irq_line)
+            .fold(0, |irq_lines, irq_line| irq_lines | (1 << irq_line))
+    }
+
+    // the irq input of the CPU
+    pub fn irq(&self) -> bool {
+        self.irq_lines() != 0
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    // the number of instructions decoded, including those whose condition was not satisfied
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown
+    }
+
+    // the word at the address without reading it through the bus, i.e. devices are peeked; None if
+    // nothing can be read at the address
+    pub fn read_memory(&self, address: u32) -> Option<u32> {
+        let address = address & 0xffff;
+        if let Some(index) = index_of(self.rom_region, address) {
+            return Some(self.rom[index]);
+        }
+        if let Some(index) = index_of(self.ram_region, address) {
+            return Some(self.ram[index]);
+        }
+
+        let device = self.device_at(address)?;
+        device.device.peek(address - device.region.start)
+    }
+
+    // sets a word in ROM or RAM; devices can not be written without a bus write
+    pub fn write_memory(&mut self, address: u32, value: u32) -> Result<(), Error> {
+        let address = address & 0xffff;
+        if let Some(index) = index_of(self.rom_region, address) {
+            self.rom[index] = value;
+        } else if let Some(index) = index_of(self.ram_region, address) {
+            self.ram[index] = value;
+        } else {
+            return Err(Error::Bare(format!(
+                "0x{:04x} is not in ROM ({}) or RAM ({})",
+                address, self.rom_region, self.ram_region
+            )));
+        }
+
+        Ok(())
+    }
+
+    // resets the registers of the CPU, as the power controller does (rst)
+    pub fn reset(&mut self) {
+        self.cpu = Cpu::default();
+        self.instruction_address = 0;
+    }
+
+    // one clock cycle; does nothing once the machine is shut down
+    pub fn cycle(&mut self) {
+        if self.shutdown {
+            return;
+        }
+
+        let irq_lines = self.irq_lines();
+        for device in &mut self.devices {
+            device.device.set_irq_lines(irq_lines);
+        }
+
+        self.flip_bits();
+        let irq = self.cpu_irq(irq_lines);
+
+        let control = self.cpu.control();
+        self.call_hooks();
+
+        // the memory and devices only decode the lowest 16 bits of the address
+        let address = control.b & 0xffff;
+        let data = if control.rd || control.wr {
+            let (kind, value) = if control.wr {
+                (AccessKind::Write, control.a)
+            } else if control.ld_ir {
+                (AccessKind::Fetch, self.corrupt_read(address))
+            } else {
+                (AccessKind::Read, self.corrupt_read(address))
+            };
+
+            let mut access = Access {
+                cycle: self.cycles,
+                kind,
+                address,
+                value,
+            };
+            for callback in &mut self.hooks.memory_access {
+                callback(&mut access);
+            }
+
+            access.value
+        } else {
+            0
+        };
+
+        let (alu_out, alu_status) = alu::operate(control.alu_op, control.a, control.b);
+        let result = if control.rd {
+            data
+        } else if control.oe_alu {
+            alu_out
+        } else {
+            0
+        };
+
+        self.bus = Bus {
+            addr: if control.rd || control.wr { address } else { 0 },
+            data,
+            rd: control.rd,
+            wr: control.wr,
+        };
+
+        if control.ld_ir {
+            self.instruction_address = self.cpu.pc;
+        }
+
+        self.cpu.clock(&control, result, alu_status, irq);
+        self.clock_devices(&control, address, data);
+        self.cycles += 1;
+    }
+
+    // runs until the next instruction is about to be fetched, i.e. the current instruction and any
+    // interrupt it causes are finished
+    pub fn step(&mut self) {
+        self.cycle();
+        while self.cpu.state != State::Fetch && !self.shutdown {
+            self.cycle();
+        }
+    }
+
+    // steps until the condition is true or the machine is shut down; the condition is checked
+    // before each step
+    pub fn run_until(&mut self, mut condition: impl FnMut(&Self) -> bool) -> Stop {
+        loop {
+            if self.shutdown {
+                return Stop::Shutdown;
+            }
+            if condition(self) {
+                return Stop::Condition;
+            }
+
+            self.step();
+        }
+    }
+
+    fn call_hooks(&mut self) {
+        match self.cpu.state {
+            State::Decode => {
+                self.instructions += 1;
+                let execute = Execute {
+                    cycle: self.cycles,
+                    address: self.instruction_address,
+                    word: self.cpu.ir,
+                    executed: self.cpu.satisfies_condition(),
+                };
+                for callback in &mut self.hooks.execute {
+                    callback(&execute);
+                }
+            }
+            State::HwInt1 | State::SwInt1 | State::Except1 => {
+                let interrupt = Interrupt {
+                    cycle: self.cycles,
+                    kind: match self.cpu.state {
+                        State::HwInt1 => InterruptKind::Hardware,
+                        State::SwInt1 => InterruptKind::Software,
+                        _ => InterruptKind::Exception,
+                    },
+                    return_address: self.cpu.pc,
+                };
+                for callback in &mut self.hooks.interrupt {
+                    callback(&interrupt);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // flips the bits of the faults of this cycle
+    fn flip_bits(&mut self) {
+        for (index, fault) in self.faults.iter().enumerate() {
+            let description = match *fault {
+                Fault::FlipRegister {
+                    cycle,
+                    register,
+                    bit,
+                } if cycle == self.cycles => {
+                    let value = self.cpu.register(register);
+                    self.cpu.set_register(register, value ^ (1 << bit));
+                    format!("flipped bit {} of {}", bit, register)
+                }
+                Fault::FlipMemory {
+                    cycle,
+                    address,
+                    bit,
+                } if cycle == self.cycles => {
+                    let address = address & 0xffff;
+                    if let Some(index) = index_of(self.rom_region, address) {
+                        self.rom[index] ^= 1 << bit;
+                    } else if let Some(index) = index_of(self.ram_region, address) {
+                        self.ram[index] ^= 1 << bit;
+                    }
+                    format!("flipped bit {} of 0x{:04x}", bit, address)
+                }
+                _ => continue,
+            };
+
+            self.injections.push(Injection {
+                cycle: self.cycles,
+                fault: index,
+                description,
+            });
+        }
+    }
+
+    // the irq input of the CPU, which is low while interrupts are dropped; an interrupt is only
+    // recorded as dropped if the CPU would have taken it in this cycle
+    fn cpu_irq(&mut self, irq_lines: u16) -> bool {
+        let irq = irq_lines != 0;
+        let Some(index) = self.faults.iter().position(|fault| {
+            matches!(fault, Fault::DropInterrupts { .. }) && fault.is_active(self.cycles)
+        }) else {
+            return irq;
+        };
+
+        if self.cpu.next_state(irq) == State::HwInt1 && self.cpu.next_state(false) != State::HwInt1
+        {
+            self.injections.push(Injection {
+                cycle: self.cycles,
+                fault: index,
+                description: format!(
+                    "dropped a hardware interrupt (irq lines 0x{:04x})",
+                    irq_lines
+                ),
+            });
+        }
+
+        false
+    }
+
+    // a read on the bus, with the value corrupted by the faults of this cycle
+    fn corrupt_read(&mut self, address: u32) -> u32 {
+        let mut value = self.bus_read(address);
+        for (index, fault) in self.faults.iter().enumerate() {
+            let Fault::CorruptRead {
+                address: fault_address,
+                mask,
+                ..
+            } = *fault
+            else {
+                continue;
+            };
+
+            if !fault.is_active(self.cycles)
+                || fault_address.is_some_and(|fault_address| fault_address & 0xffff != address)
+            {
+                continue;
+            }
+
+            self.injections.push(Injection {
+                cycle: self.cycles,
+                fault: index,
+                description: format!(
+                    "corrupted the read of 0x{:04x} from 0x{:08x} to 0x{:08x}",
+                    address,
+                    value,
+                    value ^ mask
+                ),
+            });
+            value ^= mask;
+        }
+
+        value
+    }
+
+    fn device_at(&self, address: u32) -> Option<&MappedDevice> {
+        self.devices
+            .iter()
+            .find(|device| index_of(device.region, address).is_some())
+    }
+
+    // memory and devices that aren't mapped to the address don't drive the data bus
+    fn bus_read(&mut self, address: u32) -> u32 {
+        if let Some(index) = index_of(self.rom_region, address) {
+            return self.rom[index];
+        }
+        if let Some(index) = index_of(self.ram_region, address) {
+            return self.ram[index];
+        }
+
+        match self
+            .devices
+            .iter_mut()
+            .find(|device| index_of(device.region, address).is_some())
+        {
+            Some(device) => device.device.read(address - device.region.start),
+            None => 0,
+        }
+    }
+
+    fn clock_devices(&mut self, control: &Control, address: u32, data: u32) {
+        if control.wr
+            && let Some(index) = index_of(self.ram_region, address)
+        {
+            self.ram[index] = data;
+        }
+
+        let mut power = None;
+        for device in &mut self.devices {
+            device.device.clock();
+            if control.wr
+                && let Some(offset) = index_of(device.region, address)
+            {
+                device.device.write(offset as u32, data);
+            }
+
+            power = power.or(device.device.power());
+        }
+
+        match power {
+            Some(Power::Shutdown) => self.shutdown = true,
+            Some(Power::Restart) => self.reset(),
+            None => (),
+        }
+    }
+}
+
+// the index of the address in the region
+fn index_of(region: Region, address: u32) -> Option<usize> {
+    (address >= region.start && address < region.end()).then(|| (address - region.start) as usize)
+}
+
+// parses the contents of a memory as read by $readmemh, i.e. the machine code the assembler writes,
+// or vmem with @address records, which are relative to the start of the region
+pub fn parse_memory(memory: &str, region: Region) -> Result<Vec<Segment>, Error> {
+    let mut segments = vec![Segment {
+        address: region.start,
+        words: Vec::new(),
+    }];
+
+    for (line_number, line) in memory.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default();
+
+        for token in line.split_whitespace() {
+            let invalid = |error| {
+                Error::Bare(format!(
+                    "Invalid word \"{}\" on line {}: {}",
+                    token,
+                    line_number + 1,
+                    error
+                ))
+            };
+
+            if let Some(address) = token.strip_prefix('@') {
+                let offset = u32::from_str_radix(address, 16).map_err(invalid)?;
+                let Some(address) = region.start.checked_add(offset) else {
+                    return Err(Error::Bare(format!(
+                        "Words at 0x{:04x} are not in {}",
+                        region.start as u64 + offset as u64,
+                        region
+                    )));
+                };
+                segments.push(Segment {
+                    address,
+                    words: Vec::new(),
+                });
+            } else {
+                let word = u32::from_str_radix(token, 16).map_err(invalid)?;
+                if let Some(segment) = segments.last_mut() {
+                    segment.words.push(word);
+                }
+            }
+        }
+    }
+
+    segments.retain(|segment| !segment.words.is_empty());
+    if let Some(segment) = segments.iter().find(|segment| !region.contains(segment)) {
+        return Err(Error::Bare(format!(
+            "Words at {} are not in {}",
+            segment, region
+        )));
+    }
+
+    Ok(segments)
+}
@@ -0,0 +1,85 @@
+// the operations of alu.sv, which are done on the a and b buses every cycle, even if the result is
+// not used (e.g. the status loaded when writing to the status register in user mode)
+
+pub(crate) const PASS: u32 = 0x0;
+const AND: u32 = 0x1;
+const OR: u32 = 0x2;
+const XOR: u32 = 0x3;
+const NOT: u32 = 0x4;
+const ADD: u32 = 0x5;
+const SUB: u32 = 0x6;
+const NEG: u32 = 0x7;
+const SHL: u32 = 0x8;
+const SHR: u32 = 0x9;
+const ASHR: u32 = 0xa;
+const ROL: u32 = 0xb;
+const ROR: u32 = 0xc;
+
+// the bits of alu_status_t, in the order they are in the status register
+pub(crate) const NEGATIVE: u32 = 1 << 3;
+pub(crate) const ZERO: u32 = 1 << 2;
+pub(crate) const CARRY: u32 = 1 << 1;
+pub(crate) const OVERFLOW: u32 = 1 << 0;
+
+// returns the output and the 4 status bits (negative, zero, carry, overflow)
+pub(crate) fn operate(operation: u32, a: u32, b: u32) -> (u32, u32) {
+    let (out, carry, overflow) = match operation {
+        PASS => (b, false, false),
+        AND => (a & b, false, false),
+        OR => (a | b, false, false),
+        XOR => (a ^ b, false, false),
+        NOT => (!b, false, false),
+        ADD => add(a, b),
+        SUB => add(a, b.wrapping_neg()),
+        NEG => (b.wrapping_neg(), false, false),
+
+        // the shifts are on 33 bits, where the extra bit is the carry
+        SHL => {
+            let shifted = if b > 32 { 0 } else { (a as u64) << b };
+            (shifted as u32, shifted & (1 << 32) != 0, false)
+        }
+        SHR => {
+            let shifted = ((a as u64) << 1).checked_shr(b).unwrap_or(0);
+            ((shifted >> 1) as u32, shifted & 1 != 0, false)
+        }
+        ASHR => {
+            let shifted = ((a as i32 as i64) << 1) >> b.min(63);
+            ((shifted >> 1) as u32, shifted & 1 != 0, false)
+        }
+
+        // the rotates only use the lowest 5 bits of b
+        ROL => {
+            let rotated = (((a as u64) << 32) | a as u64) >> (32 - (b & 0x1f));
+            (rotated as u32, rotated & (1 << 32) != 0, false)
+        }
+        ROR => {
+            let rotated = (((a as u128) << 33) | ((a as u128) << 1)) >> (b & 0x1f);
+            ((rotated >> 1) as u32, rotated & 1 != 0, false)
+        }
+
+        _ => (0, false, false),
+    };
+
+    let mut status = 0;
+    if out & (1 << 31) != 0 {
+        status |= NEGATIVE;
+    }
+    if out == 0 {
+        status |= ZERO;
+    }
+    if carry {
+        status |= CARRY;
+    }
+    if overflow {
+        status |= OVERFLOW;
+    }
+
+    (out, status)
+}
+
+// full_adder.sv
+fn add(a: u32, b: u32) -> (u32, bool, bool) {
+    let (out, carry) = a.overflowing_add(b);
+    let overflow = ((out ^ a ^ b) >> 31 != 0) ^ carry;
+    (out, carry, overflow)
+}
@@ -0,0 +1,383 @@
+// the registers and control unit of cpu.sv
+// the control unit is split into the signals of the current state (`control`), and what is loaded
+// at the clock edge (`clock`), so the machine can put the memory and devices between them
+
+use std::fmt::{self, Display};
+
+use crate::{ast::Register, emulator::alu};
+
+// the bits of status_t
+pub(crate) const ALU_STATUS_SHIFT: u32 = 2;
+pub(crate) const IMASK: u32 = 1 << 1;
+pub(crate) const USER_MODE: u32 = 1 << 0;
+const STATUS_MASK: u32 = 0x3f;
+
+// the vectors loaded into pc by each interrupt (reg_constants.sv)
+const HARDWARE_VECTOR: u32 = 1;
+const SOFTWARE_VECTOR: u32 = 2;
+const EXCEPTION_VECTOR: u32 = 3;
+
+// the states of cu.sv; one instruction takes several states
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum State {
+    #[default]
+    Fetch,
+    Decode,
+
+    // execute states
+    Nop,
+    Ld,
+    Ldr,
+    Ldi,
+    St,
+    Str,
+    Push,
+    Pop,
+    Alu,
+
+    HwInt1,
+    HwInt2,
+    HwInt3,
+
+    SwInt1,
+    SwInt2,
+    SwInt3,
+
+    Except1,
+    Except2,
+    Except3,
+}
+
+impl State {
+    // the value of the state in cu.sv (states_e)
+    pub fn code(&self) -> u32 {
+        *self as u32
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            State::Fetch => "FETCH",
+            State::Decode => "DECODE",
+            State::Nop => "NOP",
+            State::Ld => "LD",
+            State::Ldr => "LDR",
+            State::Ldi => "LDI",
+            State::St => "ST",
+            State::Str => "STR",
+            State::Push => "PUSH",
+            State::Pop => "POP",
+            State::Alu => "ALU",
+            State::HwInt1 => "HWINT1",
+            State::HwInt2 => "HWINT2",
+            State::HwInt3 => "HWINT3",
+            State::SwInt1 => "SWINT1",
+            State::SwInt2 => "SWINT2",
+            State::SwInt3 => "SWINT3",
+            State::Except1 => "EXCEPT1",
+            State::Except2 => "EXCEPT2",
+            State::Except3 => "EXCEPT3",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// every register resets to 0 (rst)
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct Cpu {
+    pub(crate) registers: [u32; 11], // r0 to r10 (fp)
+    pub(crate) status: u32,
+    pub(crate) sp: u32,
+    pub(crate) lr: u32,
+    pub(crate) pc: u32,
+    pub(crate) ir: u32,
+    pub(crate) state: State,
+}
+
+// the outputs of the control unit in the current state, with the a and b buses they select
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct Control {
+    pub(crate) a: u32,
+    pub(crate) b: u32,
+    pub(crate) rd: bool,
+    pub(crate) wr: bool,
+    pub(crate) oe_alu: bool,
+    pub(crate) alu_op: u32,
+    pub(crate) load: Option<u32>, // sel_in_reg, if ld_reg is set
+    pub(crate) ld_ir: bool,
+    pub(crate) pre_dec_sp: bool,
+    pub(crate) post_inc_sp: bool,
+    pub(crate) post_inc_pc: bool,
+    pub(crate) ld_alu_status: bool,
+    pub(crate) interrupt: bool, // imask <- 0, mode <- SUPERVISOR
+}
+
+impl Cpu {
+    pub(crate) fn register(&self, register: Register) -> u32 {
+        match register {
+            Register::Status => self.status,
+            Register::Sp => self.sp,
+            Register::Lr => self.lr,
+            Register::Pclink | Register::Pc => self.pc,
+            register => self.registers[register as usize],
+        }
+    }
+
+    pub(crate) fn set_register(&mut self, register: Register, value: u32) {
+        match register {
+            Register::Status => self.status = value & STATUS_MASK,
+            Register::Sp => self.sp = value,
+            Register::Lr => self.lr = value,
+            Register::Pclink | Register::Pc => self.pc = value,
+            register => self.registers[register as usize] = value,
+        }
+    }
+
+    // the value a register drives onto a bus; sp is already decremented while pushing
+    fn read(&self, code: u32, pre_dec_sp: bool) -> u32 {
+        match code & 0xf {
+            0xb => self.status,
+            0xc => self.sp.wrapping_sub(pre_dec_sp as u32),
+            0xd => self.lr,
+            0xe | 0xf => self.pc,
+            code => self.registers[code as usize],
+        }
+    }
+
+    pub(crate) fn control(&self) -> Control {
+        let ir = self.ir;
+        let reg_a = (ir >> 16) & 0xf;
+        let reg_b = (ir >> 12) & 0xf;
+        // the offset of LDR and STR is a signed 12 bit value
+        let offset = (((ir << 20) as i32) >> 20) as u32;
+
+        let mut control = Control::default();
+        match self.state {
+            // ir <- *(pc++)
+            State::Fetch => {
+                control.b = self.pc;
+                control.rd = true;
+                control.ld_ir = true;
+                control.post_inc_pc = true;
+            }
+
+            State::Decode | State::Nop => (),
+
+            // reg_a <- *address
+            State::Ld => {
+                control.b = ir & 0xffff;
+                control.rd = true;
+                control.load = Some(reg_a);
+            }
+
+            // reg_a <- *reg_b
+            State::Ldr => {
+                control.b = self.read(reg_b, false).wrapping_add(offset);
+                control.rd = true;
+                control.load = Some(reg_a);
+            }
+
+            // reg_a <- immediate
+            State::Ldi => {
+                control.b = ir & 0xffff;
+                control.alu_op = alu::PASS;
+                control.oe_alu = true;
+                control.load = Some(reg_a);
+            }
+
+            // *address <- reg_a
+            State::St => {
+                control.a = self.read(reg_a, false);
+                control.b = ir & 0xffff;
+                control.wr = true;
+            }
+
+            // *reg_b <- reg_a
+            State::Str => {
+                control.a = self.read(reg_a, false);
+                control.b = self.read(reg_b, false).wrapping_add(offset);
+                control.wr = true;
+            }
+
+            // *(--sp) <- reg_a
+            State::Push => {
+                control.pre_dec_sp = true;
+                control.a = self.read(reg_a, true);
+                control.b = self.read(Register::Sp as u32, true);
+                control.wr = true;
+            }
+
+            // reg_a <- *(sp++)
+            State::Pop => {
+                control.b = self.sp;
+                control.rd = true;
+                control.load = Some(reg_a);
+                control.post_inc_sp = true;
+            }
+
+            State::Alu => {
+                let immediate = ir & (1 << 19) != 0;
+                let reverse = ir & (1 << 18) != 0;
+                let loadn = ir & (1 << 17) != 0;
+                let set_status = ir & (1 << 16) != 0;
+                let reg_a = (ir >> 12) & 0xf;
+                let reg_b = self.read(ir >> 8, false);
+
+                (control.a, control.b) = match (immediate, reverse) {
+                    (true, true) => (ir & 0xff, reg_b),
+                    (true, false) => (reg_b, ir & 0xff),
+                    (false, true) => (self.read(ir >> 4, false), reg_b),
+                    (false, false) => (reg_b, self.read(ir >> 4, false)),
+                };
+
+                // the alu op is always the second nibble of the instruction
+                control.alu_op = (ir >> 20) & 0xf;
+                control.oe_alu = !loadn;
+                control.load = (!loadn).then_some(reg_a);
+                control.ld_alu_status = set_status;
+            }
+
+            // push pc
+            // imask <- 0
+            // mode <- SUPERVISOR
+            State::HwInt1 | State::SwInt1 | State::Except1 => {
+                control.pre_dec_sp = true;
+                control.a = self.pc;
+                control.b = self.read(Register::Sp as u32, true);
+                control.wr = true;
+                control.interrupt = true;
+            }
+
+            // push status
+            State::HwInt2 | State::SwInt2 | State::Except2 => {
+                control.pre_dec_sp = true;
+                control.a = self.status;
+                control.b = self.read(Register::Sp as u32, true);
+                control.wr = true;
+            }
+
+            // pc <- vector
+            State::HwInt3 | State::SwInt3 | State::Except3 => {
+                control.b = match self.state {
+                    State::HwInt3 => HARDWARE_VECTOR,
+                    State::SwInt3 => SOFTWARE_VECTOR,
+                    _ => EXCEPTION_VECTOR,
+                };
+                control.alu_op = alu::PASS;
+                control.oe_alu = true;
+                control.load = Some(Register::Pc as u32);
+            }
+        }
+
+        control
+    }
+
+    // the clock edge; result is the value on the result bus (the data read, or the alu output)
+    pub(crate) fn clock(&mut self, control: &Control, result: u32, alu_status: u32, irq: bool) {
+        let state = self.next_state(irq);
+        let pc = self.pc;
+        let mut sp = self.sp;
+        let mut status = self.status;
+
+        if let Some(register) = control.load {
+            match register {
+                0xb if self.status & USER_MODE == 0 => status = result & STATUS_MASK,
+                // writing the status register in user mode loads the status of the alu instead
+                0xb => status = with_alu_status(status, alu_status),
+                0xc => sp = result,
+                0xd => self.lr = result,
+                // pc.link also loads lr with pc
+                0xe => {
+                    self.lr = pc;
+                    self.pc = result;
+                }
+                0xf => self.pc = result,
+                register => self.registers[register as usize] = result,
+            }
+        }
+
+        if control.ld_alu_status {
+            status = with_alu_status(status, alu_status);
+        }
+
+        if control.interrupt {
+            status &= !(IMASK | USER_MODE);
+        }
+
+        self.status = status;
+        self.sp = sp
+            .wrapping_add(control.post_inc_sp as u32)
+            .wrapping_sub(control.pre_dec_sp as u32);
+        self.pc = self.pc.wrapping_add(control.post_inc_pc as u32);
+
+        if control.ld_ir {
+            self.ir = result;
+        }
+
+        self.state = state;
+    }
+
+    pub(crate) fn next_state(&self, irq: bool) -> State {
+        let interrupt = irq && self.status & IMASK != 0;
+
+        match self.state {
+            State::Fetch => State::Decode,
+            State::Decode if self.satisfies_condition() => match (self.ir >> 20) & 0xff {
+                0x00 => State::Nop,
+                0x01 => State::Ld,
+                0x02 => State::Ldr,
+                0x03 => State::Ldi,
+                0x04 => State::St,
+                0x05 => State::Str,
+                0x06 => State::Push,
+                0x07 => State::Pop,
+                0x08 => State::SwInt1,
+                // all alu ops have an f in the first nibble of the instruction
+                0xf0..=0xff => State::Alu,
+                _ => State::Except1,
+            },
+
+            State::SwInt1 => State::SwInt2,
+            State::SwInt2 => State::SwInt3,
+            State::HwInt1 => State::HwInt2,
+            State::HwInt2 => State::HwInt3,
+            State::Except1 => State::Except2,
+            State::Except2 => State::Except3,
+
+            _ if interrupt => State::HwInt1,
+            _ => State::Fetch,
+        }
+    }
+
+    // whether the condition of the instruction in ir is satisfied by the status register
+    pub(crate) fn satisfies_condition(&self) -> bool {
+        let alu_status = self.status >> ALU_STATUS_SHIFT;
+        let negative = alu_status & alu::NEGATIVE != 0;
+        let zero = alu_status & alu::ZERO != 0;
+        let carry = alu_status & alu::CARRY != 0;
+        let overflow = alu_status & alu::OVERFLOW != 0;
+
+        match self.ir >> 28 {
+            0x1 => zero,
+            0x2 => !zero,
+            0x3 => negative,
+            0x4 => !negative,
+            0x5 => overflow,
+            0x6 => !overflow,
+            0x7 => !carry,
+            0x8 => carry && !zero,
+            0x9 => !carry || zero,
+            0xa => carry,
+            0xb => negative != overflow,
+            0xc => !zero && negative == overflow,
+            0xd => zero || negative != overflow,
+            0xe => negative == overflow,
+            _ => true,
+        }
+    }
+}
+
+fn with_alu_status(status: u32, alu_status: u32) -> u32 {
+    (status & !(0xf << ALU_STATUS_SHIFT)) | (alu_status << ALU_STATUS_SHIFT)
+}
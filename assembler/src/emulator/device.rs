@@ -0,0 +1,45 @@
+use std::any::Any;
+
+// a memory mapped device, which is given every access to the addresses it is mapped to
+// registers are addressed by their offset from the address of the device
+//
+// every method has a default, so a device only implements what it uses, e.g. a device that is only
+// written to only implements `write`
+pub trait Device: Any {
+    // the value driven onto the data bus when the register is read
+    fn read(&mut self, _offset: u32) -> u32 {
+        0
+    }
+
+    // the value of the register without reading it, for debuggers; None if reading would change the
+    // device, or the register can't be read
+    fn peek(&self, _offset: u32) -> Option<u32> {
+        None
+    }
+
+    // called at the clock edge of a cycle that writes to the register, after `clock`, so a write
+    // takes priority over anything the device does by itself in the same cycle
+    fn write(&mut self, _offset: u32, _value: u32) {}
+
+    // called at every clock edge
+    fn clock(&mut self) {}
+
+    // the level of the irq line of the device, if it is connected to the interrupt controller
+    fn irq(&self) -> bool {
+        false
+    }
+
+    // called before every cycle with the irq line of every device; used by interrupt controllers
+    fn set_irq_lines(&mut self, _irq_lines: u16) {}
+
+    // a request to shut down or restart the machine, checked after every cycle
+    fn power(&mut self) -> Option<Power> {
+        None
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Power {
+    Shutdown,
+    Restart, // only the registers of the CPU are reset
+}
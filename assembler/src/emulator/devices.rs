@@ -0,0 +1,142 @@
+// the devices of simulator.sv (see docs/cpu/simulator.md)
+
+use crate::emulator::device::{Device, Power};
+
+// timer.sv
+#[derive(Debug, Clone, Default)]
+pub struct Timer {
+    ctrl: u32,
+    interval: u32,
+    timer: u32,
+    timeout: bool,
+}
+
+impl Timer {
+    const ACK: u32 = 0b00;
+    const CTRL: u32 = 0b01;
+    const INTERVAL: u32 = 0b10;
+    const TIMER: u32 = 0b11;
+
+    const START: u32 = 0b01;
+    const CONTINUE: u32 = 0b10;
+}
+
+impl Device for Timer {
+    fn read(&mut self, offset: u32) -> u32 {
+        self.peek(offset).unwrap_or(0)
+    }
+
+    fn peek(&self, offset: u32) -> Option<u32> {
+        match offset {
+            Self::ACK => Some(0),
+            Self::CTRL => Some(self.ctrl),
+            Self::INTERVAL => Some(self.interval),
+            Self::TIMER => Some(self.timer),
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, offset: u32, value: u32) {
+        match offset {
+            Self::ACK => self.timeout = false,
+            Self::CTRL => self.ctrl = value & (Self::START | Self::CONTINUE),
+            Self::INTERVAL => self.interval = value,
+            Self::TIMER => self.timer = value,
+            _ => (),
+        }
+    }
+
+    fn clock(&mut self) {
+        if self.ctrl & Self::START == 0 {
+            return;
+        }
+
+        let timer = self.timer;
+        self.timer = timer.wrapping_sub(1);
+
+        // need to check the timer is 0 in case it started at 0
+        if timer == 0 || timer == 1 {
+            self.timeout = true;
+
+            if self.ctrl & Self::CONTINUE != 0 {
+                self.timer = self.interval;
+            } else {
+                self.ctrl &= !Self::START;
+            }
+        }
+    }
+
+    fn irq(&self) -> bool {
+        self.timeout
+    }
+}
+
+// ic.sv; reading gives the irq line of every device
+#[derive(Debug, Clone, Default)]
+pub struct InterruptController {
+    irq_lines: u16,
+}
+
+impl Device for InterruptController {
+    fn read(&mut self, _offset: u32) -> u32 {
+        self.irq_lines as u32
+    }
+
+    fn peek(&self, _offset: u32) -> Option<u32> {
+        Some(self.irq_lines as u32)
+    }
+
+    fn set_irq_lines(&mut self, irq_lines: u16) {
+        self.irq_lines = irq_lines;
+    }
+}
+
+// power.sv
+#[derive(Debug, Clone, Default)]
+pub struct PowerController {
+    request: Option<Power>,
+}
+
+impl PowerController {
+    const SHUTDOWN: u32 = 0;
+    const RESTART: u32 = 1;
+}
+
+impl Device for PowerController {
+    fn write(&mut self, _offset: u32, value: u32) {
+        // only the lowest 2 bits of the data bus are connected
+        self.request = match value & 0b11 {
+            Self::SHUTDOWN => Some(Power::Shutdown),
+            Self::RESTART => Some(Power::Restart),
+            _ => None,
+        };
+    }
+
+    fn power(&mut self) -> Option<Power> {
+        self.request.take()
+    }
+}
+
+// tty.sv; the characters written are kept until they are taken
+#[derive(Debug, Clone, Default)]
+pub struct Tty {
+    output: Vec<u8>,
+}
+
+impl Tty {
+    // every byte written since the output was last taken
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+}
+
+impl Device for Tty {
+    fn write(&mut self, _offset: u32, value: u32) {
+        // only the lowest 8 bits of the data bus are connected
+        self.output.push(value as u8);
+    }
+}
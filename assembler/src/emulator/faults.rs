@@ -0,0 +1,110 @@
+// faults the machine injects while it runs, to check that a program (e.g. with a watchdog) still
+// reaches its expected output
+// a fault campaign is described by a TOML file:
+//
+// expected_output = "0123456789\n"
+//
+// [[faults]]
+// kind = "flip_register"
+// cycle = 100
+// register = "r0"
+// bit = 3
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::{ast::Register, disassembler::instruction::decode_register, error::Error};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Campaign {
+    // the tty output the program should write despite the faults
+    pub expected_output: Option<String>,
+
+    #[serde(default)]
+    pub faults: Vec<Fault>,
+}
+
+// the cycles of a fault are the values of `Machine::cycles` when it applies; windows include both
+// the start and end cycles, and have no end if end is not given
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Fault {
+    // flips a bit of the register before the cycle
+    FlipRegister {
+        cycle: u64,
+        #[serde(deserialize_with = "register")]
+        register: Register,
+        bit: u32,
+    },
+
+    // flips a bit of a word in ROM or RAM before the cycle
+    FlipMemory {
+        cycle: u64,
+        address: u32,
+        bit: u32,
+    },
+
+    // holds the irq input of the CPU low, so hardware interrupts are not taken
+    DropInterrupts {
+        #[serde(default)]
+        start: u64,
+        end: Option<u64>,
+    },
+
+    // xors the mask into the value of reads and fetches, of any address if address is not given
+    CorruptRead {
+        #[serde(default)]
+        start: u64,
+        end: Option<u64>,
+        address: Option<u32>,
+        mask: u32,
+    },
+}
+
+// a change to the machine made by a fault
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Injection {
+    pub cycle: u64,
+    pub fault: usize, // the index of the fault, in the order the faults were added
+    pub description: String,
+}
+
+impl Campaign {
+    pub fn parse(campaign: &str) -> Result<Self, Error> {
+        toml::from_str(campaign)
+            .map_err(|error| Error::Bare(format!("Invalid fault description: {}", error)))
+    }
+}
+
+impl Fault {
+    // whether a window fault applies in the cycle
+    pub(crate) fn is_active(&self, cycle: u64) -> bool {
+        match *self {
+            Fault::DropInterrupts { start, end } | Fault::CorruptRead { start, end, .. } => {
+                cycle >= start && end.is_none_or(|end| cycle <= end)
+            }
+            Fault::FlipRegister { .. } | Fault::FlipMemory { .. } => false,
+        }
+    }
+}
+
+impl Display for Injection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cycle {}: {} (fault {})",
+            self.cycle, self.description, self.fault
+        )
+    }
+}
+
+// registers are named as they are in assembly, e.g. "r0", "fp", or "sp"
+fn register<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Register, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    (0..16)
+        .map(decode_register)
+        .find(|register| register.to_string() == name)
+        .ok_or_else(|| de::Error::custom(format!("unknown register \"{}\"", name)))
+}
@@ -0,0 +1,270 @@
+// writes a value change dump of a machine, with the same hierarchy and signal names as a dump of
+// simulator.sv, so the two can be compared in the same waveform viewer
+// the clock has the same period as simulator.sv (20 time units, with the first rising edge at 10)
+
+use std::io::{self, Write};
+
+use crate::{
+    disassembler::instruction::decode_register,
+    emulator::{Bus, Machine, Register, State},
+};
+
+const HALF_PERIOD: u64 = 10;
+
+// the identifier of the clock, which is the first signal
+const CLK: &str = "!";
+
+// the values of a cycle, before its clock edge
+struct Snapshot {
+    registers: [u32; 16],
+    ir: u32,
+    state: State,
+    irq_lines: u16,
+    bus: Bus,
+}
+
+impl Snapshot {
+    // the bus is only known once the cycle is run
+    fn new(machine: &Machine) -> Self {
+        let mut registers = [0; 16];
+        for (code, value) in registers.iter_mut().enumerate() {
+            *value = machine.register(decode_register(code as u32));
+        }
+
+        Self {
+            registers,
+            ir: machine.ir(),
+            state: machine.state(),
+            irq_lines: machine.irq_lines(),
+            bus: Bus::default(),
+        }
+    }
+}
+
+struct Signal {
+    scope: Vec<String>,
+    name: String,
+    width: u32,
+    id: String,
+    value: Box<dyn Fn(&Snapshot) -> u64>,
+    last: Option<u64>,
+}
+
+pub struct Vcd<W: Write> {
+    writer: W,
+    signals: Vec<Signal>,
+    cycles: u64,
+}
+
+impl<W: Write> Vcd<W> {
+    // writes the header; the irq line of each device of the machine is named after the device, e.g.
+    // timer_int
+    pub fn new(mut writer: W, machine: &Machine) -> io::Result<Self> {
+        let mut signals = Vec::new();
+        let mut add = |scope: &[&str], name: &str, width, value: Box<dyn Fn(&Snapshot) -> u64>| {
+            signals.push(Signal {
+                scope: scope.iter().map(|scope| scope.to_string()).collect(),
+                name: name.to_string(),
+                width,
+                id: identifier(signals.len()),
+                value,
+                last: None,
+            })
+        };
+
+        // simulator.sv
+        let top = ["simulator"];
+        add(&top, "clk", 1, Box::new(|_| 0));
+        add(&top, "irq", 1, Box::new(|s| (s.irq_lines != 0) as u64));
+        add(&top, "addr", 32, Box::new(|s| s.bus.addr as u64));
+        add(&top, "data", 32, Box::new(|s| s.bus.data as u64));
+        add(&top, "rd", 1, Box::new(|s| s.bus.rd as u64));
+        add(&top, "wr", 1, Box::new(|s| s.bus.wr as u64));
+        add(&top, "irq_sources", 16, Box::new(|s| s.irq_lines as u64));
+        for (name, _, irq_line) in machine.devices() {
+            if let Some(irq_line) = irq_line {
+                let name = format!("{}_int", name);
+                add(
+                    &top,
+                    &name,
+                    1,
+                    Box::new(move |s| (s.irq_lines >> irq_line) as u64 & 1),
+                );
+            }
+        }
+
+        // cpu.sv
+        let cpu = ["simulator", "cpu0"];
+        add(&cpu, "ir", 32, Box::new(|s| s.ir as u64));
+        add(&cpu, "status", 6, Box::new(|s| status(s) as u64));
+        add(&cpu, "pc_val", 32, Box::new(|s| pc(s) as u64));
+        add(
+            &["simulator", "cpu0", "cu0"],
+            "state",
+            32,
+            Box::new(|s| s.state.code() as u64),
+        );
+
+        for register in 0..=10 {
+            let generate = format!("g_registers[{}]", register);
+            add(
+                &["simulator", "cpu0", "reg_file", &generate, "register"],
+                "value",
+                32,
+                Box::new(move |s| s.registers[register] as u64),
+            );
+        }
+
+        // the fields of status_t
+        let status_reg = ["simulator", "cpu0", "status_reg"];
+        add(&status_reg, "value", 6, Box::new(|s| status(s) as u64));
+        for (bit, name) in ["mode", "imask", "overflow", "carry", "zero", "negative"]
+            .iter()
+            .enumerate()
+        {
+            add(
+                &status_reg,
+                name,
+                1,
+                Box::new(move |s| (status(s) >> bit) as u64 & 1),
+            );
+        }
+
+        add(
+            &["simulator", "cpu0", "sp"],
+            "value_reg",
+            32,
+            Box::new(|s| s.registers[Register::Sp as usize] as u64),
+        );
+        add(
+            &["simulator", "cpu0", "lr"],
+            "value",
+            32,
+            Box::new(|s| s.registers[Register::Lr as usize] as u64),
+        );
+        add(
+            &["simulator", "cpu0", "pc"],
+            "value",
+            32,
+            Box::new(|s| pc(s) as u64),
+        );
+
+        writeln!(writer, "$version ablomm_emu $end")?;
+        writeln!(writer, "$timescale 1ns $end")?;
+
+        let mut scope: Vec<String> = Vec::new();
+        for signal in &signals {
+            let common = scope
+                .iter()
+                .zip(&signal.scope)
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            for _ in common..scope.len() {
+                writeln!(writer, "$upscope $end")?;
+            }
+            for name in &signal.scope[common..] {
+                writeln!(writer, "$scope module {} $end", name)?;
+            }
+            scope = signal.scope.clone();
+
+            match signal.width {
+                1 => writeln!(writer, "$var wire 1 {} {} $end", signal.id, signal.name)?,
+                width => writeln!(
+                    writer,
+                    "$var wire {} {} {} [{}:0] $end",
+                    width,
+                    signal.id,
+                    signal.name,
+                    width - 1
+                )?,
+            }
+        }
+        for _ in &scope {
+            writeln!(writer, "$upscope $end")?;
+        }
+        writeln!(writer, "$enddefinitions $end")?;
+
+        Ok(Self {
+            writer,
+            signals,
+            cycles: 0,
+        })
+    }
+
+    // runs one cycle of the machine and writes it
+    pub fn cycle(&mut self, machine: &mut Machine) -> io::Result<()> {
+        let mut snapshot = Snapshot::new(machine);
+        machine.cycle();
+        snapshot.bus = machine.bus();
+
+        // the values of a cycle start at the previous rising edge (or at 0 for the first cycle)
+        if self.cycles == 0 {
+            writeln!(self.writer, "#0\n$dumpvars\n0{}", CLK)?;
+            self.write_changes(&snapshot)?;
+            writeln!(self.writer, "$end")?;
+        } else {
+            let rising_edge = rising_edge(self.cycles - 1);
+            writeln!(self.writer, "#{}\n1{}", rising_edge, CLK)?;
+            self.write_changes(&snapshot)?;
+            writeln!(self.writer, "#{}\n0{}", rising_edge + HALF_PERIOD, CLK)?;
+        }
+
+        self.cycles += 1;
+        Ok(())
+    }
+
+    // writes the rising edge of the last cycle, so the registers it loaded are shown
+    pub fn finish(mut self, machine: &Machine) -> io::Result<W> {
+        if self.cycles != 0 {
+            writeln!(self.writer, "#{}\n1{}", rising_edge(self.cycles - 1), CLK)?;
+            self.write_changes(&Snapshot::new(machine))?;
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_changes(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        // the clock is the first signal, and is written at each edge instead
+        for signal in self.signals.iter_mut().skip(1) {
+            let value = (signal.value)(snapshot);
+            if signal.last == Some(value) {
+                continue;
+            }
+            signal.last = Some(value);
+
+            match signal.width {
+                1 => writeln!(self.writer, "{}{}", value, signal.id)?,
+                _ => writeln!(self.writer, "b{:b} {}", value, signal.id)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn rising_edge(cycle: u64) -> u64 {
+    HALF_PERIOD + 2 * HALF_PERIOD * cycle
+}
+
+fn status(snapshot: &Snapshot) -> u32 {
+    snapshot.registers[Register::Status as usize]
+}
+
+fn pc(snapshot: &Snapshot) -> u32 {
+    snapshot.registers[Register::Pc as usize]
+}
+
+// identifiers are made of the printable characters from ! to ~
+fn identifier(mut index: usize) -> String {
+    let mut identifier = String::new();
+    loop {
+        identifier.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return identifier;
+        }
+        index -= 1;
+    }
+}
@@ -3,7 +3,12 @@ use std::{
     io::{self, Write},
 };
 
-use crate::{Span, expression::expression_result::ExpressionResult, span::Spanned, src::Src};
+use crate::{
+    Span,
+    expression::expression_result::{ExpressionResult, RelativeTo},
+    span::Spanned,
+    src::Src,
+};
 use ariadne::{Color, Fmt};
 use internment::Intern;
 
@@ -120,6 +125,14 @@ impl Error {
         )))
     }
 
+    pub(crate) fn unknown_value(span: Span, waiting_spans: impl IntoIterator<Item = Span>) -> Self {
+        Self::Spanned(Box::new(SpannedError::unknown_value(span, waiting_spans)))
+    }
+
+    pub(crate) fn relocatable(span: Span, relative_to: &RelativeTo) -> Self {
+        Self::Spanned(Box::new(SpannedError::relocatable(span, relative_to)))
+    }
+
     pub(crate) fn identifier_already_defined(
         define1: Span,
         define1_import: Option<Span>,
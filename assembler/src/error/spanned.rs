@@ -5,8 +5,12 @@ use std::{
 };
 
 use crate::{
-    Span, error::ATTENTION_COLOR, expression::expression_result::ExpressionResult, span::Spanned,
-    src::Src, utils,
+    Span,
+    error::ATTENTION_COLOR,
+    expression::expression_result::{ExpressionResult, RelativeTo},
+    span::Spanned,
+    src::Src,
+    utils,
 };
 use ariadne::{Cache, Color, Fmt};
 use chumsky::error::{RichPattern, RichReason};
@@ -159,6 +163,30 @@ impl SpannedError {
             .with_label(format!("Expected {}, but found {}", expected, found,))
     }
 
+    pub(crate) fn unknown_value(span: Span, waiting_spans: impl IntoIterator<Item = Span>) -> Self {
+        let mut error = SpannedError::new(span, "Unknown value of expression")
+            .with_label("Expression needs to be determined, but is not");
+        for waiting_span in waiting_spans {
+            error = error.with_label_span(waiting_span, "This value is undetermined")
+        }
+
+        error.with_note(
+            "This is ultimately caused because the expression is dependent on a future address (label), but the value of the expression would effect that address (label)",
+        ).with_note(
+            "For more info, see https://github.com/ablomm/ablomm-cpu/blob/main/docs/assembler/errors.md#unknown-value-of-expression",
+        )
+    }
+
+    // a number that isn't known until linking was used in a way the linker can't patch
+    pub(crate) fn relocatable(span: Span, relative_to: &RelativeTo) -> Self {
+        SpannedError::new(span, "Relocatable number")
+            .with_label(format!(
+                "This is relative to {}, which is not known until the object is linked",
+                relative_to.to_string().fg(ATTENTION_COLOR)
+            ))
+            .with_help("Only a number can be added to or subtracted from this")
+    }
+
     pub(crate) fn identifier_already_defined(
         define1: Span,
         define1_import: Option<Span>,
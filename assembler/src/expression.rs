@@ -48,7 +48,7 @@ impl Spanned<&Expression> {
         let result = match self.val {
             Expression::Register(register) => ExpressionResult::Register(Some(*register)),
             Expression::String(string) => ExpressionResult::String(Some(String(string.clone()))),
-            Expression::Number(number) => ExpressionResult::Number(Some(Number(*number))),
+            Expression::Number(number) => ExpressionResult::Number(Some(Number(*number, None))),
             Expression::Identifier(identifier) => {
                 let entry = symbol_table.try_get(&self.span_to(identifier))?;
                 check_for_loops(&entry, self.span, loop_check)?;
@@ -3,6 +3,8 @@ use std::{
     ops::{Add, BitAnd, BitOr, BitXor, Deref, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub},
 };
 
+use internment::Intern;
+
 use crate::{ast::Register, error::Error, span::Spanned};
 
 mod indirect;
@@ -24,8 +26,27 @@ pub(crate) enum ExpressionResult {
 }
 
 // newtypes
+// the relative_to is only set when assembling an object, for numbers that are not known until the
+// object is placed by the linker (i.e. labels, and symbols imported from other files)
 #[derive(Debug, Clone, Copy)]
-pub(crate) struct Number(pub(crate) u32);
+pub(crate) struct Number(pub(crate) u32, pub(crate) Option<RelativeTo>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RelativeTo {
+    Rom,                                 // the start of the object's words in ROM
+    Ram,                                 // the start of the object's words in RAM
+    Symbol(Intern<std::string::String>), // a symbol exported by another object
+}
+
+impl Display for RelativeTo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelativeTo::Rom => write!(f, "the start of the object in ROM"),
+            RelativeTo::Ram => write!(f, "the start of the object in RAM"),
+            RelativeTo::Symbol(symbol) => write!(f, "the address of {}", symbol),
+        }
+    }
+}
 
 impl Deref for Number {
     type Target = u32;
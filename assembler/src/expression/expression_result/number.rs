@@ -9,13 +9,26 @@ use crate::{
     span::Spanned,
 };
 
+impl Spanned<&Number> {
+    // the value of a number that isn't relative to where the linker places something, as most
+    // operations on an address that isn't known yet can't be patched by the linker
+    pub(crate) fn absolute(&self) -> Result<u32, Error> {
+        match self.1 {
+            Some(relative_to) => Err(Error::relocatable(self.span, &relative_to)),
+            None => Ok(**self.val),
+        }
+    }
+}
+
 impl Neg for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn neg(self) -> Self::Output {
         if let Some(val) = self.val {
+            let val = self.span_to(val).absolute()?;
             Ok(ExpressionResult::Number(Some(Number(
-                -(**val as i32) as u32,
+                -(val as i32) as u32,
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -28,7 +41,8 @@ impl Not for &Spanned<&Option<Number>> {
 
     fn not(self) -> Self::Output {
         if let Some(val) = self.val {
-            Ok(ExpressionResult::Number(Some(Number(!**val))))
+            let val = self.span_to(val).absolute()?;
+            Ok(ExpressionResult::Number(Some(Number(!val, None))))
         } else {
             Ok(ExpressionResult::Number(None))
         }
@@ -50,9 +64,12 @@ impl Mul<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn mul(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_mul(**rhs),
+                lhs.wrapping_mul(rhs),
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -76,8 +93,9 @@ impl Div<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
 
     fn div(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
         if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
-            let rhs = rhs.span_to(rhs_val);
-            if **rhs.val == 0 {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs.span_to(rhs_val).absolute()?);
+            if rhs.val == 0 {
                 return Err(Error::Spanned(Box::new(
                     SpannedError::new(rhs.span, "Division by 0").with_label(format!(
                         "Cannot divide by {}, and expression evaluates to {}",
@@ -87,7 +105,8 @@ impl Div<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
                 )));
             }
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_div(**rhs.val),
+                lhs.wrapping_div(rhs.val),
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -111,8 +130,9 @@ impl Rem<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
 
     fn rem(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
         if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
-            let rhs = rhs.span_to(rhs_val);
-            if **rhs.val == 0 {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs.span_to(rhs_val).absolute()?);
+            if rhs.val == 0 {
                 return Err(Error::Spanned(Box::new(
                     SpannedError::new(rhs.span, "Remainder by 0").with_label(format!(
                         "Cannot take remainder by {}, and expression evaluates to {}",
@@ -122,7 +142,8 @@ impl Rem<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
                 )));
             }
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_rem(**rhs.val),
+                lhs.wrapping_rem(rhs.val),
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -151,9 +172,18 @@ impl Add<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn add(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            // only one side can be relative, as the sum of two addresses is meaningless
+            let relative_to = match (lhs.1, rhs_val.1) {
+                (Some(_), Some(relative_to)) => {
+                    return Err(Error::relocatable(rhs.span, &relative_to));
+                }
+                (lhs_relative_to, rhs_relative_to) => lhs_relative_to.or(rhs_relative_to),
+            };
+
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_add(**rhs),
+                lhs.wrapping_add(**rhs_val),
+                relative_to,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -166,6 +196,7 @@ impl Add<&Spanned<&Option<String>>> for &Spanned<&Option<Number>> {
 
     fn add(self, rhs: &Spanned<&Option<String>>) -> Self::Output {
         if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
             Ok(ExpressionResult::String(Some(String(
                 lhs.to_string() + rhs,
             ))))
@@ -208,9 +239,22 @@ impl Sub<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn sub(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            // the distance between two numbers relative to the same thing is known (e.g. the length
+            // between two labels), but nothing can be relative to the negative of something
+            let relative_to = match (lhs.1, rhs_val.1) {
+                (lhs_relative_to, None) => lhs_relative_to,
+                (Some(lhs_relative_to), Some(rhs_relative_to))
+                    if lhs_relative_to == rhs_relative_to =>
+                {
+                    None
+                }
+                (_, Some(relative_to)) => return Err(Error::relocatable(rhs.span, &relative_to)),
+            };
+
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_sub(**rhs),
+                lhs.wrapping_sub(**rhs_val),
+                relative_to,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -233,9 +277,12 @@ impl Shl<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn shl(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_shl(**rhs),
+                lhs.wrapping_shl(rhs),
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -258,9 +305,12 @@ impl Shr<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn shr(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
             Ok(ExpressionResult::Number(Some(Number(
-                lhs.wrapping_shr(**rhs),
+                lhs.wrapping_shr(rhs),
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -283,9 +333,12 @@ impl Ashr<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn ashr(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
             Ok(ExpressionResult::Number(Some(Number(
-                (**lhs as i32).wrapping_shr(**rhs) as u32,
+                (lhs as i32).wrapping_shr(rhs) as u32,
+                None,
             ))))
         } else {
             Ok(ExpressionResult::Number(None))
@@ -308,8 +361,10 @@ impl BitAnd<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn bitand(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
-            Ok(ExpressionResult::Number(Some(Number(**lhs & **rhs))))
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
+            Ok(ExpressionResult::Number(Some(Number(lhs & rhs, None))))
         } else {
             Ok(ExpressionResult::Number(None))
         }
@@ -331,8 +386,10 @@ impl BitOr<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn bitor(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
-            Ok(ExpressionResult::Number(Some(Number(**lhs | **rhs))))
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
+            Ok(ExpressionResult::Number(Some(Number(lhs | rhs, None))))
         } else {
             Ok(ExpressionResult::Number(None))
         }
@@ -354,8 +411,10 @@ impl BitXor<&Spanned<&Option<Number>>> for &Spanned<&Option<Number>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn bitxor(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
-            Ok(ExpressionResult::Number(Some(Number(**lhs ^ **rhs))))
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let lhs = self.span_to(lhs).absolute()?;
+            let rhs = rhs.span_to(rhs_val).absolute()?;
+            Ok(ExpressionResult::Number(Some(Number(lhs ^ rhs, None))))
         } else {
             Ok(ExpressionResult::Number(None))
         }
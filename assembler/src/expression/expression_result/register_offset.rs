@@ -21,8 +21,9 @@ impl Add<&Spanned<&Option<Number>>> for &Spanned<&Option<RegisterOffset>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn add(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
-            let new_offset = (lhs.offset as u32).wrapping_add(**rhs) as i32;
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let rhs = rhs.span_to(rhs_val).absolute()?;
+            let new_offset = (lhs.offset as u32).wrapping_add(rhs) as i32;
 
             Ok(ExpressionResult::RegisterOffset(Some(RegisterOffset {
                 reg: Spanned::new(*lhs.reg, lhs.reg.span),
@@ -49,8 +50,9 @@ impl Sub<&Spanned<&Option<Number>>> for &Spanned<&Option<RegisterOffset>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn sub(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
-            let new_offset = (lhs.offset as u32).wrapping_sub(**rhs) as i32;
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let rhs = rhs.span_to(rhs_val).absolute()?;
+            let new_offset = (lhs.offset as u32).wrapping_sub(rhs) as i32;
 
             Ok(ExpressionResult::RegisterOffset(Some(RegisterOffset {
                 reg: Spanned::new(*lhs.reg, lhs.reg.span),
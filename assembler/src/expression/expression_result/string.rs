@@ -22,7 +22,8 @@ impl Add<&Spanned<&Option<Number>>> for &Spanned<&Option<String>> {
     type Output = Result<ExpressionResult, Error>;
 
     fn add(self, rhs: &Spanned<&Option<Number>>) -> Self::Output {
-        if let (Some(lhs), Some(rhs)) = (self.val, rhs.val) {
+        if let (Some(lhs), Some(rhs_val)) = (self.val, rhs.val) {
+            let rhs = rhs.span_to(rhs_val).absolute()?;
             Ok(ExpressionResult::String(Some(String(
                 lhs.to_string() + &rhs.to_string(),
             ))))
@@ -6,6 +6,7 @@ use internment::Intern;
 
 use crate::{
     SrcCache,
+    assembly::LoadImage,
     ast::{Ast, Block, File, Statement},
     error::{Error, RecoveredError, RecoveredResult, SpannedError},
     parser,
@@ -15,12 +16,25 @@ use crate::{
 
 impl Spanned<Intern<Src>> {
     // takes Src and returns the full Ast assuming Src is the root file
-    pub(super) fn build_ast(&self, cache: &mut SrcCache) -> RecoveredResult<Ast> {
+    pub(super) fn build_ast(&self, cache: &mut SrcCache, object: bool) -> RecoveredResult<Ast> {
         let files = self
-            .build_file_queue(cache, &mut HashSet::new())
-            .map_err(|RecoveredError(files, errors)| RecoveredError(Ast { files }, errors))?;
-
-        Ok(Ast { files })
+            .build_file_queue(cache, &mut HashSet::new(), object)
+            .map_err(|RecoveredError(files, errors)| {
+                RecoveredError(
+                    Ast {
+                        files,
+                        object,
+                        load_image: LoadImage::default(),
+                    },
+                    errors,
+                )
+            })?;
+
+        Ok(Ast {
+            files,
+            object,
+            load_image: LoadImage::default(),
+        })
     }
 
     // gets all the files for a given Src and returns it in the correct order for generation
@@ -28,6 +42,7 @@ impl Spanned<Intern<Src>> {
         &self,
         cache: &mut SrcCache,
         src_map: &mut HashSet<Intern<Src>>,
+        object: bool,
     ) -> RecoveredResult<Vec<Spanned<File>>> {
         let mut file_queue = Vec::new();
         let mut errors = Vec::new();
@@ -47,7 +62,7 @@ impl Spanned<Intern<Src>> {
 
         src_map.insert(self.val);
 
-        let mut sub_files = match file.as_mut_ref().get_import_files(cache, src_map) {
+        let mut sub_files = match file.as_mut_ref().get_import_files(cache, src_map, object) {
             Ok(sub_files) => sub_files,
             Err(RecoveredError(sub_files, mut file_errors)) => {
                 errors.append(&mut file_errors);
@@ -111,10 +126,11 @@ impl Spanned<&mut File> {
         &mut self,
         cache: &mut SrcCache,
         src_map: &mut HashSet<Intern<Src>>,
+        object: bool,
     ) -> RecoveredResult<Vec<Spanned<File>>> {
         self.span
             .spanned(&mut self.block)
-            .get_import_files(cache, src_map)
+            .get_import_files(cache, src_map, object)
     }
 }
 
@@ -124,12 +140,16 @@ impl Spanned<&mut Block> {
         &mut self,
         cache: &mut SrcCache,
         src_map: &mut HashSet<Intern<Src>>,
+        object: bool,
     ) -> RecoveredResult<Vec<Spanned<File>>> {
         let mut files = Vec::new();
         let mut errors = Vec::new();
 
         self.statements.retain_mut(|statement| {
-            match statement.as_mut_ref().get_import_files(cache, src_map) {
+            match statement
+                .as_mut_ref()
+                .get_import_files(cache, src_map, object)
+            {
                 Ok(mut statement_files) => {
                     files.append(&mut statement_files);
                     true
@@ -137,8 +157,8 @@ impl Spanned<&mut Block> {
                 Err(RecoveredError(mut statement_files, mut statement_errors)) => {
                     // false if this import, for whatever reason, did not generate a file (e.g. os read error),
                     // so we need to remove the import so we don't try to use it in subsequent steps
-                    let should_retain_statement =
-                        matches!(statement.val, Statement::Import(_)) && statement_files.is_empty();
+                    let should_retain_statement = !matches!(statement.val, Statement::Import(_))
+                        || !statement_files.is_empty();
                     files.append(&mut statement_files);
                     errors.append(&mut statement_errors);
                     should_retain_statement
@@ -160,17 +180,23 @@ impl Spanned<&mut Statement> {
         &mut self,
         cache: &mut SrcCache,
         src_map: &mut HashSet<Intern<Src>>,
+        object: bool,
     ) -> RecoveredResult<Vec<Spanned<File>>> {
         match &mut self.val {
             Statement::Import(import) => {
-                if src_map.contains(&import.src) {
-                    // we already did this import
+                // an object only includes the target module, as other files are linked separately
+                let linked = object && *import.src.val != Src::target_module();
+                if src_map.contains(&import.src) || linked {
+                    // we already did this import, or the linker will
                     Ok(Vec::new())
                 } else {
-                    import.src.build_file_queue(cache, src_map)
+                    import.src.build_file_queue(cache, src_map, object)
                 }
             }
-            Statement::Block(block) => self.span.spanned(block).get_import_files(cache, src_map),
+            Statement::Block(block) => self
+                .span
+                .spanned(block)
+                .get_import_files(cache, src_map, object),
             _ => Ok(Vec::new()),
         }
     }
@@ -1,86 +1,292 @@
+use crate::assembly::{AssembledStatement, Section, StatementKind};
 use crate::ast::{
     AluCpuMnemonic, AluModifier, AluOpFlags, AsmMnemonic, BinaryAluCpuMnemonic, Block, Condition,
     CpuMnemonic, Expression, File, Modifier, Operation, Register, UnaryAluCpuMnemonic,
 };
 use crate::ast::{Ast, Statement};
 use crate::error::{ATTENTION_COLOR, Error, RecoveredError, RecoveredResult, SpannedError};
-use crate::expression::expression_result::ExpressionResult;
+use crate::expression::expression_result::{ExpressionResult, Number};
+use crate::object::{Startup, Value};
 use crate::span::Spanned;
 use crate::symbol_table::SymbolTable;
+use crate::target::Target;
 use ariadne::Fmt;
 use std::fmt::Display;
 use std::ops::Range;
 
 mod alu_op;
+mod image;
 mod int;
 mod ld;
 mod nop;
+mod object;
+mod placement;
 mod pop;
 mod push;
+mod regions;
+mod startup;
+
+pub(crate) use image::{Cursor, Image};
+pub(crate) use startup::{STARTUP_WORDS, startup_words};
 
 impl Ast {
     // if errors, it will return an errors with a recovered program
-    pub(super) fn generate(&self) -> RecoveredResult<Vec<u32>> {
-        let mut opcodes = Vec::new();
+    // also returns each statement with the addresses of the words it generated
+    #[allow(clippy::result_large_err)] // only returned once per assembly
+    pub(super) fn generate(
+        &self,
+        target: &Target,
+    ) -> RecoveredResult<(Image, Vec<AssembledStatement>)> {
+        let mut image = Image::new(target, self.load_image.clone());
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+
+        let mut cursor = Cursor::new(target);
+        for file in &self.files {
+            if let Err(mut file_errors) =
+                file.as_ref()
+                    .generate(&mut cursor, &mut image, &mut statements)
+            {
+                errors.append(&mut file_errors);
+            }
+        }
+
+        // the load image is after all the other words in ROM
+        if image.has_startup {
+            let words = image.load_image_words();
+            let mut cursor = Cursor::new(target);
+            cursor.advance(image.load_image.address - target.rom.start);
+            image.place(&mut cursor, &words);
+        }
+
+        if errors.is_empty() {
+            Ok((image, statements))
+        } else {
+            Err(RecoveredError((image, statements), errors))
+        }
+    }
+
+    // the generated words and the absolute addresses of every ld must be in the memories of the
+    // target
+    pub(super) fn check(
+        &self,
+        image: &Image,
+        statements: &[AssembledStatement],
+        target: &Target,
+    ) -> Vec<Error> {
+        let mut errors = Vec::new();
 
         for file in &self.files {
-            opcodes.append(&mut file.as_ref().generate()?);
+            errors.append(&mut file.as_ref().check_addresses(target));
         }
+        errors.append(&mut regions::check_placement(
+            statements,
+            image.has_startup.then_some(&image.load_image),
+            target,
+        ));
 
-        Ok(opcodes)
+        errors
     }
 }
 
 impl Spanned<&File> {
-    fn generate(&self) -> RecoveredResult<Vec<u32>> {
-        self.span_to(&self.block).generate()
+    fn generate(
+        &self,
+        cursor: &mut Cursor,
+        image: &mut Image,
+        statements: &mut Vec<AssembledStatement>,
+    ) -> Result<(), Vec<Error>> {
+        // each file starts in the text section
+        cursor.section = Section::default();
+        self.span_to(&self.block)
+            .generate(cursor, 0, image, statements)
     }
 }
 
 impl Spanned<&Block> {
-    fn generate(&self) -> RecoveredResult<Vec<u32>> {
-        let mut opcodes = Vec::new();
+    fn generate(
+        &self,
+        cursor: &mut Cursor,
+        depth: usize,
+        image: &mut Image,
+        statements: &mut Vec<AssembledStatement>,
+    ) -> Result<(), Vec<Error>> {
         let mut errors = Vec::new();
 
         for statement in &self.statements {
-            let mut sub_opcodes = match statement.as_ref().generate(&self.symbol_table.borrow()) {
-                Ok(sub_opcodes) => sub_opcodes,
-                Err(RecoveredError(sub_opcodes, mut sub_errors)) => {
-                    errors.append(&mut sub_errors);
-                    sub_opcodes
-                }
-            };
+            let statement_address = cursor.address();
+
+            // pushed before generating so that a block comes before the statements in it
+            let index = statements.len();
+            if let Some(kind) = statement.kind() {
+                statements.push(AssembledStatement {
+                    kind,
+                    section: cursor.section,
+                    span: statement.span,
+                    addresses: statement_address..statement_address,
+                    depth,
+                });
+            }
+
+            if let Err(mut statement_errors) = statement.as_ref().generate(
+                &self.symbol_table.borrow(),
+                cursor,
+                depth,
+                image,
+                statements,
+            ) {
+                errors.append(&mut statement_errors);
+            }
 
-            opcodes.append(&mut sub_opcodes);
+            if let Some(assembled) = statements.get_mut(index) {
+                // a section statement is placed at the start of the section it changes to
+                if assembled.section != cursor.section {
+                    assembled.section = cursor.section;
+                    assembled.addresses.start = cursor.address();
+                }
+                assembled.addresses.end = cursor.address();
+            }
         }
 
         if errors.is_empty() {
-            Ok(opcodes)
+            Ok(())
         } else {
-            Err(RecoveredError(opcodes, errors))
+            Err(errors)
         }
     }
 }
 
+impl Spanned<Statement> {
+    fn kind(&self) -> Option<StatementKind> {
+        Some(match self.val {
+            Statement::Block(_) => StatementKind::Block,
+            Statement::Operation(_) => StatementKind::Operation,
+            Statement::Label(_) => StatementKind::Label,
+            Statement::Assignment(_) => StatementKind::Assignment,
+            Statement::GenLiteral(_) => StatementKind::GenLiteral,
+            Statement::Export(_) => StatementKind::Export,
+            Statement::Import(_) => StatementKind::Import,
+            Statement::Org(_) => StatementKind::Org,
+            Statement::Align(_) => StatementKind::Align,
+            Statement::Section(_) => StatementKind::Section,
+            Statement::Reserve(_) => StatementKind::Reserve,
+            Statement::Startup(_) => StatementKind::Startup,
+            Statement::Error => return None,
+        })
+    }
+}
+
 impl Spanned<&Statement> {
-    fn generate(&self, symbol_table: &SymbolTable) -> RecoveredResult<Vec<u32>> {
-        match &self.val {
-            Statement::Operation(operation) => self
-                .span_to(operation)
-                .generate(symbol_table)
-                .map_err(|error| RecoveredError(Vec::new(), vec![error])),
-            Statement::Block(block) => self.span_to(block).generate(),
-            Statement::GenLiteral(literal) => self
-                .span_to(literal)
-                .generate(symbol_table)
-                .map_err(|error| RecoveredError(Vec::new(), vec![error])),
-            _ => Ok(vec![]),
+    fn generate(
+        &self,
+        symbol_table: &SymbolTable,
+        cursor: &mut Cursor,
+        depth: usize,
+        image: &mut Image,
+        statements: &mut Vec<AssembledStatement>,
+    ) -> Result<(), Vec<Error>> {
+        let words = match &self.val {
+            Statement::Operation(operation) => self.span_to(operation).generate(symbol_table),
+            Statement::GenLiteral(literal) => self.span_to(literal).generate(symbol_table),
+            Statement::Block(block) => {
+                // the section only lasts until the end of the block
+                let section = cursor.section;
+                let result = self
+                    .span_to(block)
+                    .generate(cursor, depth + 1, image, statements);
+                cursor.section = section;
+                return result;
+            }
+            Statement::Org(org) => {
+                let org = self.span_to(org);
+                org.org_gap(cursor.address(), symbol_table)
+                    .and_then(|gap| org.generate(gap, cursor.section, symbol_table))
+            }
+            Statement::Align(align) => {
+                let align = self.span_to(align);
+                if let Some(object) = &mut image.object {
+                    align
+                        .align_object(object, cursor.section, symbol_table)
+                        .map_err(|error| vec![error])?;
+                }
+
+                align
+                    .align_gap(cursor.address(), symbol_table)
+                    .and_then(|gap| align.generate(gap, cursor.section, symbol_table))
+            }
+            Statement::Section(section) => {
+                cursor.section = *section;
+                return Ok(());
+            }
+            Statement::Startup(entry) => {
+                image.has_startup = true;
+                match &mut image.object {
+                    // the linker generates the startup words once the load image of every object is
+                    // known, so they are left as 0
+                    Some(object) => {
+                        startup::startup_entry(&entry.as_ref(), symbol_table).map(|entry| {
+                            object.startup = Some(Startup {
+                                offset: cursor.address(),
+                                entry: Value::from(&entry.val),
+                            });
+                            Words::Generated(vec![0; STARTUP_WORDS as usize])
+                        })
+                    }
+                    None => startup::generate_startup(
+                        &entry.as_ref(),
+                        cursor.address(),
+                        &image.load_image,
+                        symbol_table,
+                    )
+                    .map(Words::Generated),
+                }
+            }
+            Statement::Reserve(size) => {
+                size.as_ref().generate_reserve(cursor.section, symbol_table)
+            }
+            _ => return Ok(()),
         }
+        .map_err(|error| vec![error])?;
+
+        match words {
+            Words::Generated(words) => {
+                if cursor.section == Section::Bss && !words.is_empty() {
+                    // still placed so that the addresses after it match the labels
+                    image.place(cursor, &words);
+                    return Err(vec![Error::Spanned(Box::new(
+                        SpannedError::new(self.span, "Words in bss section")
+                            .with_label(format!(
+                                "The {} section is not initialized, so it cannot contain words",
+                                "bss".fg(ATTENTION_COLOR)
+                            ))
+                            .with_help("Try placing this in the data section instead"),
+                    ))]);
+                }
+
+                if let Some(object) = &mut image.object {
+                    let relocation = self
+                        .relocation(&words, cursor, symbol_table)
+                        .map_err(|error| vec![error])?;
+                    object.relocations.extend(relocation);
+                }
+
+                image.place(cursor, &words);
+            }
+            Words::Reserved(num_words) => cursor.advance(num_words),
+        }
+
+        Ok(())
     }
 }
 
+// the words a statement generates, or the number of words it reserves without generating them
+enum Words {
+    Generated(Vec<u32>),
+    Reserved(u32),
+}
+
 impl Spanned<&Operation> {
-    fn generate(&self, symbol_table: &SymbolTable) -> Result<Vec<u32>, Error> {
+    fn generate(&self, symbol_table: &SymbolTable) -> Result<Words, Error> {
         match self.full_mnemonic.mnemonic.val {
             AsmMnemonic::Nop => nop::generate_nop(self),
             AsmMnemonic::Ld => ld::generate_ld(self, symbol_table),
@@ -90,19 +296,19 @@ impl Spanned<&Operation> {
             AsmMnemonic::UnaryAlu(_) => alu_op::generate_unary_alu_op(self, symbol_table),
             AsmMnemonic::BinaryAlu(_) => alu_op::generate_alu_op(self, symbol_table),
         }
-        .map(|opcode| vec![opcode])
+        .map(|opcode| Words::Generated(vec![opcode]))
     }
 }
 
 impl Spanned<&Expression> {
-    fn generate(&self, symbol_table: &SymbolTable) -> Result<Vec<u32>, Error> {
+    fn generate(&self, symbol_table: &SymbolTable) -> Result<Words, Error> {
         let result = self.span_to(self.eval(symbol_table)?.result);
 
         match result.val {
             ExpressionResult::Number(number) => {
                 // should never panic because generate occurs after symbol table is filled
                 let number = number.expect("Number value is unknown");
-                Ok(vec![*number])
+                Ok(Words::Generated(vec![*number]))
             }
             ExpressionResult::String(string) => {
                 // should never panic because generate occurs after symbol table is filled
@@ -117,7 +323,7 @@ impl Spanned<&Expression> {
                     }
                     opcodes.push(opcode);
                 }
-                Ok(opcodes)
+                Ok(Words::Generated(opcodes))
             }
             _ => Err(Error::incorrect_type(
                 vec!["number", "string"],
@@ -220,6 +426,16 @@ fn assert_range<T: Display + PartialOrd>(
     Ok(())
 }
 
+// numbers relative to where the linker places something are checked by the linker instead, once
+// their value is known
+fn assert_number_range(number: &Spanned<&Number>, range: Range<u32>) -> Result<(), Error> {
+    if number.1.is_some() {
+        return Ok(());
+    }
+
+    assert_range(&number.as_u32().copied(), range)
+}
+
 trait Generatable {
     fn generate(&self) -> u32;
 }
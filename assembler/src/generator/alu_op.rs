@@ -245,7 +245,7 @@ fn generate_alu_op_3_reg_reg_num(
     register2: &Spanned<&Register>,
     number: &Spanned<&Number>,
 ) -> Result<u32, Error> {
-    generator::assert_range(&number.as_u32().copied(), 0..(1 << 8))?;
+    generator::assert_number_range(number, 0..(1 << 8))?;
 
     let mut opcode = 0;
     opcode |= mnemonic.generate();
@@ -0,0 +1,187 @@
+use crate::{
+    assembly::{LoadImage, Section},
+    expression::expression_result::RelativeTo,
+    object::Object,
+    output::Segment,
+    target::Target,
+};
+
+// where the next statement is placed, i.e. the current section and the next address of each region
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Cursor {
+    pub(crate) section: Section,
+    rom: u32,
+    ram: u32,
+
+    // the start and end of the words generated in the data section so far
+    data: Option<(u32, u32)>,
+
+    // objects start at address 0 of each region, as they are placed by the linker
+    relocatable: bool,
+}
+
+impl Cursor {
+    pub(crate) fn new(target: &Target) -> Self {
+        Self {
+            section: Section::default(),
+            rom: target.rom.start,
+            ram: target.ram.start,
+            data: None,
+            relocatable: false,
+        }
+    }
+
+    pub(crate) fn object() -> Self {
+        Self {
+            section: Section::default(),
+            rom: 0,
+            ram: 0,
+            data: None,
+            relocatable: true,
+        }
+    }
+
+    pub(crate) fn is_relocatable(&self) -> bool {
+        self.relocatable
+    }
+
+    // what the address is relative to, if it is in an object
+    pub(crate) fn relative_to(&self) -> Option<RelativeTo> {
+        if !self.relocatable {
+            None
+        } else if self.section.in_ram() {
+            Some(RelativeTo::Ram)
+        } else {
+            Some(RelativeTo::Rom)
+        }
+    }
+
+    pub(crate) fn address(&self) -> u32 {
+        if self.section.in_ram() {
+            self.ram
+        } else {
+            self.rom
+        }
+    }
+
+    // the address after the last word in RAM
+    pub(crate) fn ram_address(&self) -> u32 {
+        self.ram
+    }
+
+    pub(crate) fn advance(&mut self, num_words: u32) {
+        if self.section.in_ram() {
+            self.ram += num_words;
+        } else {
+            self.rom += num_words;
+        }
+    }
+
+    // marks the next words as initialized, so that they are included in the load image if they are in
+    // the data section
+    pub(crate) fn initialize(&mut self, num_words: u32) {
+        if self.section != Section::Data || num_words == 0 {
+            return;
+        }
+
+        let address = self.address();
+        let (start, _) = self.data.unwrap_or((address, address));
+        self.data = Some((start, address + num_words));
+    }
+
+    // the load image is placed after all the other words in ROM
+    pub(crate) fn load_image(&self) -> LoadImage {
+        let (start, end) = self.data.unwrap_or((self.ram, self.ram));
+        LoadImage {
+            address: self.rom,
+            data: start..end,
+        }
+    }
+}
+
+// the words generated for each region
+// the ROM is a single segment starting at the start of the ROM, but the RAM is only the parts that
+// are initialized, so that reserved words aren't written to it
+#[derive(Debug, Clone)]
+pub(crate) struct Image {
+    pub(crate) rom: Segment,
+    pub(crate) ram: Vec<Segment>,
+
+    // the load image is only placed in ROM if there is a startup directive that uses it
+    pub(crate) load_image: LoadImage,
+    pub(crate) has_startup: bool,
+
+    // the relocations, exports, etc. when assembling an object; the words are filled in after
+    pub(crate) object: Option<Box<Object>>,
+}
+
+impl Image {
+    pub(crate) fn new(target: &Target, load_image: LoadImage) -> Self {
+        Self {
+            rom: Segment {
+                address: target.rom.start,
+                words: Vec::new(),
+            },
+            ram: Vec::new(),
+            load_image,
+            has_startup: false,
+            object: None,
+        }
+    }
+
+    pub(crate) fn object() -> Self {
+        Self {
+            rom: Segment {
+                address: 0,
+                words: Vec::new(),
+            },
+            ram: Vec::new(),
+            load_image: LoadImage::default(),
+            has_startup: false,
+            object: Some(Box::default()),
+        }
+    }
+
+    // places the words at the cursor and moves the cursor past them
+    pub(crate) fn place(&mut self, cursor: &mut Cursor, words: &[u32]) {
+        let address = cursor.address();
+        cursor.advance(words.len() as u32);
+
+        if cursor.section.in_ram() {
+            match self.ram.last_mut() {
+                Some(segment) if segment.end() == Some(address) => {
+                    segment.words.extend_from_slice(words)
+                }
+                _ if words.is_empty() => (),
+                _ => self.ram.push(Segment {
+                    address,
+                    words: words.to_vec(),
+                }),
+            }
+        } else {
+            // any ROM addresses skipped over (e.g. by statements with errors) are filled with 0
+            let index = (address - self.rom.address) as usize;
+            if self.rom.words.len() < index {
+                self.rom.words.resize(index, 0);
+            }
+
+            self.rom.words.extend_from_slice(words);
+        }
+    }
+
+    // the initialized data in RAM, with any reserved words in it as 0
+    pub(crate) fn load_image_words(&self) -> Vec<u32> {
+        let data = &self.load_image.data;
+        let mut words = vec![0; data.len()];
+
+        for segment in &self.ram {
+            for (address, word) in (segment.address..).zip(&segment.words) {
+                if data.contains(&address) {
+                    words[(address - data.start) as usize] = *word;
+                }
+            }
+        }
+
+        words
+    }
+}
@@ -97,7 +97,7 @@ fn generate_ld_reg_num(
     register: &Spanned<&Register>,
     number: &Spanned<&Number>,
 ) -> Result<u32, Error> {
-    generator::assert_range(&number.as_u32().copied(), 0..(1 << 16))?;
+    generator::assert_number_range(number, 0..(1 << 16))?;
 
     let mut opcode = 0;
     opcode |= generator::generate_modifiers_non_alu(modifiers)?;
@@ -166,7 +166,7 @@ fn generate_ld_reg_inum(
     register: &Spanned<&Register>,
     number: &Spanned<&Number>,
 ) -> Result<u32, Error> {
-    generator::assert_range(&number.as_u32().copied(), 0..(1 << 16))?;
+    generator::assert_number_range(number, 0..(1 << 16))?;
 
     let mut opcode = 0;
     opcode |= generator::generate_modifiers_non_alu(modifiers)?;
@@ -287,7 +287,7 @@ fn generate_ld_inum_reg(
     number: &Spanned<&Number>,
     register: &Spanned<&Register>,
 ) -> Result<u32, Error> {
-    generator::assert_range(&number.as_u32().copied(), 0..(1 << 16))?;
+    generator::assert_number_range(number, 0..(1 << 16))?;
 
     let mut opcode = 0;
     opcode |= generator::generate_modifiers_non_alu(modifiers)?;
@@ -0,0 +1,221 @@
+use indexmap::IndexMap;
+use internment::Intern;
+
+use crate::{
+    ast::{AluOpFlags, Ast, CpuMnemonic, Expression, File, Statement},
+    error::{Error, RecoveredError, RecoveredResult, SpannedError},
+    expression::expression_result::{ExpressionResult, Number, RelativeTo},
+    generator::{Cursor, Generatable, Image},
+    object::{Base, Export, Memory, Object, Relocation, RelocationKind, Value},
+    span::Spanned,
+    symbol_table::SymbolTable,
+    target::Target,
+};
+
+impl Ast {
+    // objects are generated as if they were at the start of the ROM and RAM; the linker places them
+    // and checks that they fit in the target
+    #[allow(clippy::result_large_err)] // only returned once per object
+    pub(crate) fn generate_object(&self, target: &Target) -> RecoveredResult<Object> {
+        let mut image = Image::object();
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+
+        let mut cursor = Cursor::object();
+        for file in &self.files {
+            if let Err(mut file_errors) =
+                file.as_ref()
+                    .generate(&mut cursor, &mut image, &mut statements)
+            {
+                errors.append(&mut file_errors);
+            }
+        }
+
+        for file in &self.files {
+            errors.append(&mut file.as_ref().check_addresses(target));
+        }
+
+        // should never panic because the image was created for an object
+        let mut object = *image.object.expect("Image is not an object");
+        object.rom = image.rom.words;
+        object.ram = image.ram;
+        object.ram_depth = cursor.ram_address();
+
+        // only the root file is assembled into the object; the other files are the target module
+        if let Some(root) = self.files.first() {
+            match root.as_ref().exports() {
+                Ok(exports) => object.exports = exports,
+                Err(mut export_errors) => errors.append(&mut export_errors),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(object)
+        } else {
+            Err(RecoveredError(object, errors))
+        }
+    }
+}
+
+impl Spanned<&File> {
+    // the symbols exported by the file, which the linker uses to resolve the imports of other objects
+    fn exports(&self) -> Result<Vec<Export>, Vec<Error>> {
+        let symbol_table = self.block.symbol_table.borrow();
+        let mut exports = Vec::new();
+        let mut errors = Vec::new();
+
+        let identifiers = self
+            .block
+            .statements
+            .iter()
+            .flat_map(|statement| match &statement.val {
+                Statement::Label(label) if label.export => vec![label.identifier],
+                Statement::Assignment(assignment) if assignment.export => {
+                    vec![assignment.identifier]
+                }
+                Statement::Export(identifiers) => identifiers.clone(),
+                _ => Vec::new(),
+            });
+
+        for identifier in identifiers {
+            match export(&identifier, &symbol_table) {
+                Ok(export) => exports.push(export),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(exports)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn export(
+    identifier: &Spanned<Intern<String>>,
+    symbol_table: &SymbolTable,
+) -> Result<Export, Error> {
+    let entry = symbol_table.try_get(&identifier.as_ref())?;
+    let result = entry
+        .symbol
+        .borrow_mut()
+        .try_get_result(&mut IndexMap::new())?;
+
+    match result {
+        ExpressionResult::Number(Some(number)) => Ok(Export {
+            name: identifier.val.to_string(),
+            value: Value::from(&number),
+        }),
+
+        // the importers of an object only know the names of its exports, so they are assumed to be
+        // numbers
+        result => Err(Error::Spanned(Box::new(
+            SpannedError::new(identifier.span, "Export from object")
+                .with_label(format!(
+                    "This is a {}, but only numbers and addresses can be exported from an object",
+                    result
+                ))
+                .with_help("Try assembling this file with the program instead of linking it"),
+        ))),
+    }
+}
+
+impl Spanned<&Statement> {
+    // the field of the generated word that the linker sets, if the statement has a number that
+    // is relative to where the linker places something
+    pub(super) fn relocation(
+        &self,
+        words: &[u32],
+        cursor: &Cursor,
+        symbol_table: &SymbolTable,
+    ) -> Result<Option<Relocation>, Error> {
+        let (kind, number) = match &self.val {
+            Statement::Operation(operation) => {
+                // at most one operand of an operation is a number
+                let Some(number) = operation
+                    .operands
+                    .iter()
+                    .find_map(|operand| relative_number(&operand.as_ref(), symbol_table))
+                else {
+                    return Ok(None);
+                };
+
+                let Some(kind) = words.first().and_then(|opcode| relocation_kind(*opcode)) else {
+                    // should never happen, as these are the only instructions with numbers
+                    let relative_to = number.1.expect("Number is not relative");
+                    return Err(Error::relocatable(number.span, &relative_to));
+                };
+
+                (kind, number)
+            }
+            Statement::GenLiteral(literal) => {
+                match relative_number(&self.span_to(literal), symbol_table) {
+                    Some(number) => (RelocationKind::Word, number),
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Relocation {
+            memory: if cursor.section.in_ram() {
+                Memory::Ram
+            } else {
+                Memory::Rom
+            },
+            offset: cursor.address(),
+            kind,
+            value: Value::from(&number.val),
+        }))
+    }
+}
+
+// a number that is relative to something, either directly (e.g. `label`) or as an indirect (e.g.
+// `*label`); errors in the operand were already reported when generating
+fn relative_number(
+    operand: &Spanned<&Expression>,
+    symbol_table: &SymbolTable,
+) -> Option<Spanned<Number>> {
+    let result = operand.eval(symbol_table).ok()?.result;
+
+    let number = match result {
+        ExpressionResult::Number(Some(number)) => number,
+        ExpressionResult::Indirect(indirect) => match **indirect {
+            ExpressionResult::Number(Some(number)) => number,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    number.1.is_some().then(|| operand.span_to(number))
+}
+
+// the field of the instruction that has the number, from its mnemonic
+fn relocation_kind(opcode: u32) -> Option<RelocationKind> {
+    let mnemonic = (opcode >> 20) & 0xff;
+
+    if mnemonic == CpuMnemonic::Ldi as u32 {
+        Some(RelocationKind::Immediate)
+    } else if mnemonic == CpuMnemonic::Ld as u32 || mnemonic == CpuMnemonic::St as u32 {
+        Some(RelocationKind::Address)
+    } else if mnemonic >= CpuMnemonic::Pass as u32 && opcode & AluOpFlags::Immediate.generate() != 0
+    {
+        Some(RelocationKind::AluImmediate)
+    } else {
+        None
+    }
+}
+
+impl From<&Number> for Value {
+    fn from(number: &Number) -> Self {
+        Value {
+            base: number.1.map(|relative_to| match relative_to {
+                RelativeTo::Rom => Base::Rom,
+                RelativeTo::Ram => Base::Ram,
+                RelativeTo::Symbol(symbol) => Base::Symbol(symbol.to_string()),
+            }),
+            offset: number.0,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    assembly::Section,
+    ast::{Expression, Placement},
+    error::{ATTENTION_COLOR, Error, SpannedError},
+    expression::{EvalReturn, expression_result::ExpressionResult},
+    generator::Words,
+    object::Object,
+    span::Spanned,
+    symbol_table::SymbolTable,
+    target::ADDRESS_SPACE,
+    utils,
+};
+use ariadne::Fmt;
+
+// org and align directives generate a gap of fill words up to the placed address
+impl Spanned<&Placement> {
+    // the number of words needed to reach the address of the org directive
+    pub(crate) fn org_gap(&self, address: u32, symbol_table: &SymbolTable) -> Result<u32, Error> {
+        let origin = self.operand.as_ref().known_number(symbol_table)?;
+
+        if origin.val >= ADDRESS_SPACE.end() {
+            return Err(Error::Spanned(Box::new(
+                SpannedError::new(origin.span, "Origin outside of address space").with_label(
+                    format!(
+                        "Origin is {}, but the address space is only {}",
+                        format!("0x{:x}", origin.val).fg(ATTENTION_COLOR),
+                        ADDRESS_SPACE.to_string().fg(ATTENTION_COLOR),
+                    ),
+                ),
+            )));
+        }
+
+        if origin.val < address {
+            return Err(Error::Spanned(Box::new(
+                SpannedError::new(origin.span, "Origin before current address")
+                    .with_label(format!(
+                        "Origin is {}, but the current address is already {}",
+                        format!("0x{:04x}", origin.val).fg(ATTENTION_COLOR),
+                        format!("0x{:04x}", address).fg(ATTENTION_COLOR),
+                    ))
+                    .with_help(
+                        "Try moving this code to a later origin, or moving some of the preceding code after it",
+                    ),
+            )));
+        }
+
+        Ok(origin.val - address)
+    }
+
+    // the number of words needed to reach the next address that is a multiple of the alignment
+    pub(crate) fn align_gap(&self, address: u32, symbol_table: &SymbolTable) -> Result<u32, Error> {
+        let alignment = self.alignment(symbol_table)?;
+        Ok((alignment.val - address % alignment.val) % alignment.val)
+    }
+
+    // an object is only aligned if the linker places it at a multiple of the alignment, so the
+    // object is placed at a multiple of every alignment in the same memory
+    pub(super) fn align_object(
+        &self,
+        object: &mut Object,
+        section: Section,
+        symbol_table: &SymbolTable,
+    ) -> Result<(), Error> {
+        let alignment = self.alignment(symbol_table)?;
+        let object_alignment = if section.in_ram() {
+            &mut object.ram_alignment
+        } else {
+            &mut object.rom_alignment
+        };
+
+        match utils::lcm(*object_alignment, alignment.val) {
+            Some(lcm) if lcm <= ADDRESS_SPACE.depth => {
+                *object_alignment = lcm;
+                Ok(())
+            }
+            _ => Err(Error::Spanned(Box::new(
+                SpannedError::new(alignment.span, "Incompatible alignments")
+                    .with_label(format!(
+                        "The object must be placed at a multiple of {} and {}, which is larger than the address space",
+                        alignment.val.to_string().fg(ATTENTION_COLOR),
+                        object_alignment.to_string().fg(ATTENTION_COLOR)
+                    ))
+                    .with_help("Try using alignments that are powers of 2"),
+            ))),
+        }
+    }
+
+    fn alignment(&self, symbol_table: &SymbolTable) -> Result<Spanned<u32>, Error> {
+        let alignment = self.operand.as_ref().known_number(symbol_table)?;
+
+        if alignment.val == 0 || alignment.val > ADDRESS_SPACE.depth {
+            return Err(Error::Spanned(Box::new(
+                SpannedError::new(alignment.span, "Invalid alignment").with_label(format!(
+                    "Alignment must be between 1 and {}",
+                    ADDRESS_SPACE.depth
+                )),
+            )));
+        }
+
+        Ok(alignment)
+    }
+
+    // the bss section isn't initialized, so the gap is only reserved
+    pub(super) fn generate(
+        &self,
+        gap: u32,
+        section: Section,
+        symbol_table: &SymbolTable,
+    ) -> Result<Words, Error> {
+        if section == Section::Bss {
+            return Ok(Words::Reserved(gap));
+        }
+
+        let fill = match &self.fill {
+            Some(fill) => fill.as_ref().known_number(symbol_table)?.val,
+            None => 0,
+        };
+
+        Ok(Words::Generated(vec![fill; gap as usize]))
+    }
+}
+
+// reserve directives skip over words without generating them; the expression is the number of words
+impl Spanned<&Expression> {
+    pub(crate) fn reserve_size(&self, symbol_table: &SymbolTable) -> Result<u32, Error> {
+        let size = self.known_number(symbol_table)?;
+
+        if size.val > ADDRESS_SPACE.depth {
+            return Err(Error::Spanned(Box::new(
+                SpannedError::new(size.span, "Reserve larger than address space").with_label(
+                    format!(
+                        "Reserving {} words, but the address space is only {} words",
+                        size.val.to_string().fg(ATTENTION_COLOR),
+                        ADDRESS_SPACE.depth
+                    ),
+                ),
+            )));
+        }
+
+        Ok(size.val)
+    }
+
+    // the ROM can't be written to, so there's no reason to reserve words in it
+    pub(super) fn generate_reserve(
+        &self,
+        section: Section,
+        symbol_table: &SymbolTable,
+    ) -> Result<Words, Error> {
+        if !section.in_ram() {
+            return Err(Error::Spanned(Box::new(
+                SpannedError::new(self.span, "Reserved words in ROM")
+                    .with_label(format!(
+                        "The {} section is in ROM, which cannot be written to",
+                        section.to_string().fg(ATTENTION_COLOR)
+                    ))
+                    .with_help("Try placing this in the bss section instead"),
+            )));
+        }
+
+        Ok(Words::Reserved(self.reserve_size(symbol_table)?))
+    }
+
+    // the value of an expression that must be a number and must be known when calculating labels
+    fn known_number(&self, symbol_table: &SymbolTable) -> Result<Spanned<u32>, Error> {
+        let EvalReturn {
+            result,
+            waiting_map,
+        } = self.eval(symbol_table)?;
+
+        match result {
+            ExpressionResult::Number(Some(number)) => {
+                Ok(self.span_to(self.span_to(&number).absolute()?))
+            }
+            ExpressionResult::Number(None) => Err(Error::unknown_value(
+                self.span,
+                waiting_map.values().copied(),
+            )),
+            result => Err(Error::incorrect_type(
+                vec!["number"],
+                &self.span_to(&result),
+            )),
+        }
+    }
+}
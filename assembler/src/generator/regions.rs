@@ -0,0 +1,221 @@
+use crate::{
+    assembly::{AssembledStatement, LoadImage, StatementKind},
+    ast::{AsmMnemonic, Block, File, Operation, Statement},
+    error::{ATTENTION_COLOR, Error, SpannedError},
+    expression::expression_result::ExpressionResult,
+    output::Region,
+    span::{Span, Spanned},
+    symbol_table::SymbolTable,
+    target::Target,
+};
+use ariadne::Fmt;
+
+// the words of each statement must be in the memory of its section, so that a program that grows
+// past the end of the ROM (or RAM) doesn't silently wrap around or write over a device
+// only the first statement outside of each memory is reported, as every statement after it will
+// also be outside of it
+pub(super) fn check_placement(
+    statements: &[AssembledStatement],
+    load_image: Option<&LoadImage>,
+    target: &Target,
+) -> Vec<Error> {
+    let mut errors = Vec::new();
+
+    for in_ram in [false, true] {
+        let outside = statements.iter().find(|statement| {
+            statement.kind != StatementKind::Block
+                && statement.section.in_ram() == in_ram
+                && !statement.addresses.is_empty()
+                && !statement
+                    .section
+                    .region(target)
+                    .contains_region(&words_region(
+                        statement.addresses.start,
+                        statement.addresses.len(),
+                    ))
+        });
+
+        if let Some(statement) = outside {
+            errors.push(outside_error(
+                statement.span,
+                "This",
+                words_region(statement.addresses.start, statement.addresses.len()),
+                in_ram,
+                target,
+            ));
+        }
+    }
+
+    // the load image is placed after all the other words in ROM by the startup directive
+    let startup = statements
+        .iter()
+        .find(|statement| statement.kind == StatementKind::Startup);
+    if let (Some(load_image), Some(startup)) = (load_image, startup) {
+        let words = words_region(load_image.address, load_image.data.len());
+        if errors.is_empty() && !load_image.data.is_empty() && !target.rom.contains_region(&words) {
+            errors.push(outside_error(
+                startup.span,
+                "The load image",
+                words,
+                false,
+                target,
+            ));
+        }
+    }
+
+    errors
+}
+
+fn words_region(address: u32, num_words: usize) -> Region {
+    Region {
+        start: address,
+        depth: num_words as u32,
+    }
+}
+
+// the words are described as e.g. "This" or "The load image", as in "This is at ..."
+fn outside_error(
+    span: Span,
+    description: &str,
+    words: Region,
+    in_ram: bool,
+    target: &Target,
+) -> Error {
+    let (memory, region) = if in_ram {
+        ("RAM", target.ram)
+    } else {
+        ("ROM", target.rom)
+    };
+
+    let mut error =
+        SpannedError::new(span, format!("Words outside of {}", memory)).with_label(format!(
+            "{} is at {}, but the {} of the target \"{}\" is {}",
+            description,
+            words.to_string().fg(ATTENTION_COLOR),
+            memory,
+            target.name,
+            region.to_string().fg(ATTENTION_COLOR)
+        ));
+
+    if let Some(overlapped) = overlapped_name(&words, in_ram, target) {
+        error = error.with_note(format!("This overlaps {}", overlapped));
+    }
+
+    Error::Spanned(Box::new(error.with_help(format!(
+        "Try making the {} sections smaller, or using a target with a larger {}",
+        if in_ram {
+            "data and bss"
+        } else {
+            "text and rodata"
+        },
+        memory
+    ))))
+}
+
+// the other memory or device the words are in, if any
+fn overlapped_name(words: &Region, in_ram: bool, target: &Target) -> Option<String> {
+    if in_ram && target.rom.overlaps(words) {
+        return Some(format!("the ROM ({})", target.rom));
+    }
+    if !in_ram && target.ram.overlaps(words) {
+        return Some(format!("the RAM ({})", target.ram));
+    }
+
+    target
+        .devices
+        .iter()
+        .find(|device| device.region().overlaps(words))
+        .map(|device| format!("the {} device ({})", device.name, device.region()))
+}
+
+// ld with an absolute address (e.g. `ld r0, *0x4006;` or `ld *0x4006, r0;`) must access a memory
+// or device of the target, and a store (i.e. the first operand is the absolute address) must be to
+// the RAM or a device, as the ROM can not be written
+impl Spanned<&File> {
+    pub(super) fn check_addresses(&self, target: &Target) -> Vec<Error> {
+        self.span_to(&self.block).check_addresses(target)
+    }
+}
+
+impl Spanned<&Block> {
+    fn check_addresses(&self, target: &Target) -> Vec<Error> {
+        let symbol_table = self.symbol_table.borrow();
+
+        self.statements
+            .iter()
+            .flat_map(|statement| match &statement.val {
+                Statement::Operation(operation) => statement
+                    .span_to(operation)
+                    .check_address(&symbol_table, target)
+                    .err()
+                    .into_iter()
+                    .collect(),
+                Statement::Block(block) => statement.span_to(block).check_addresses(target),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+}
+
+impl Spanned<&Operation> {
+    fn check_address(&self, symbol_table: &SymbolTable, target: &Target) -> Result<(), Error> {
+        if !matches!(self.full_mnemonic.mnemonic.val, AsmMnemonic::Ld) {
+            return Ok(());
+        }
+
+        for (i, operand) in self.operands.iter().enumerate() {
+            // errors in the operands were already reported when generating
+            let Ok(result) = operand.as_ref().eval(symbol_table) else {
+                continue;
+            };
+
+            let ExpressionResult::Indirect(indirect) = result.result else {
+                continue;
+            };
+            // in an object, an address relative to where the linker places something is not known yet
+            let ExpressionResult::Number(Some(address)) = **indirect else {
+                continue;
+            };
+            if address.1.is_some() {
+                continue;
+            }
+
+            let word = words_region(*address, 1);
+            let writable = target.ram.contains_region(&word)
+                || target
+                    .devices
+                    .iter()
+                    .any(|device| device.region().contains_region(&word));
+
+            if i == 0 && target.rom.contains_region(&word) {
+                return Err(Error::Spanned(Box::new(
+                    SpannedError::new(operand.span, "Store to ROM")
+                        .with_label(format!(
+                            "{} is in the ROM of the target \"{}\" ({}), which can not be written",
+                            format!("0x{:04x}", *address).fg(ATTENTION_COLOR),
+                            target.name,
+                            target.rom
+                        ))
+                        .with_help(format!("Try storing to the RAM ({})", target.ram)),
+                )));
+            }
+
+            if !writable && !target.rom.contains_region(&word) {
+                return Err(Error::Spanned(Box::new(
+                    SpannedError::new(operand.span, "Unmapped address")
+                        .with_label(format!(
+                            "{} is not in the ROM, RAM, or a device of the target \"{}\"",
+                            format!("0x{:04x}", *address).fg(ATTENTION_COLOR),
+                            target.name
+                        ))
+                        .with_note(format!(
+                            "The ROM is {}, and the RAM is {}",
+                            target.rom, target.ram
+                        )),
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    assembly::LoadImage,
+    ast::{AluCpuMnemonic, AluModifier, AluOpFlags, Condition, CpuMnemonic, Expression, Register},
+    error::Error,
+    expression::expression_result::{ExpressionResult, Number},
+    generator::{self, Generatable},
+    span::Spanned,
+    symbol_table::SymbolTable,
+};
+
+pub(crate) const STARTUP_WORDS: u32 = 12;
+
+// copies the load image from ROM to RAM, then jumps to the entry
+// this uses r0 to r3 and the status register, which is fine as it is meant to run at reset
+//
+//     ld r1, <load image address>;
+//     ld r2, <data start>;
+//     ld r3, <data length>;
+//     sub.t r3, 0;
+//     ld.eq pc, <entry>;
+// copy:
+//     ld r0, *r1;
+//     ld *r2, r0;
+//     add r1, 1;
+//     add r2, 1;
+//     sub.s r3, 1;
+//     ld.ne pc, copy;
+//     ld pc, <entry>;
+pub(super) fn generate_startup(
+    entry: &Spanned<&Expression>,
+    address: u32,
+    load_image: &LoadImage,
+    symbol_table: &SymbolTable,
+) -> Result<Vec<u32>, Error> {
+    let entry = startup_entry(entry, symbol_table)?;
+    let entry = entry.span_to(entry.as_ref().absolute()?);
+    generator::assert_range(&entry, 0..(1 << 16))?;
+
+    Ok(startup_words(entry.val, address, load_image))
+}
+
+// the entry of the startup directive, which is relative to something if it is in an object
+pub(super) fn startup_entry(
+    entry: &Spanned<&Expression>,
+    symbol_table: &SymbolTable,
+) -> Result<Spanned<Number>, Error> {
+    let result = entry.span_to(entry.eval(symbol_table)?.result);
+    match &result.val {
+        ExpressionResult::Number(number) => {
+            // should never panic because generate occurs after symbol table is filled
+            let number = number.expect("Number value is unknown");
+            Ok(entry.span_to(number))
+        }
+        _ => Err(Error::incorrect_type(vec!["number"], &result.as_ref())),
+    }
+}
+
+// the startup words are also generated by the linker, once the load image of every object is known
+pub(crate) fn startup_words(entry: u32, address: u32, load_image: &LoadImage) -> Vec<u32> {
+    let copy = address + 5;
+    let length = load_image.data.len() as u32;
+
+    let words = vec![
+        ldi(Condition::None, Register::R1, load_image.address),
+        ldi(Condition::None, Register::R2, load_image.data.start),
+        ldi(Condition::None, Register::R3, length),
+        alu_immediate(AluCpuMnemonic::Sub, Some(AluModifier::T), Register::R3, 0),
+        ldi(Condition::Eq, Register::Pc, entry),
+        CpuMnemonic::Ldr.generate() | Register::R0.generate() << 16 | Register::R1.generate() << 12,
+        CpuMnemonic::Str.generate() | Register::R0.generate() << 16 | Register::R2.generate() << 12,
+        alu_immediate(AluCpuMnemonic::Add, None, Register::R1, 1),
+        alu_immediate(AluCpuMnemonic::Add, None, Register::R2, 1),
+        alu_immediate(AluCpuMnemonic::Sub, Some(AluModifier::S), Register::R3, 1),
+        ldi(Condition::Ne, Register::Pc, copy),
+        ldi(Condition::None, Register::Pc, entry),
+    ];
+
+    debug_assert_eq!(words.len() as u32, STARTUP_WORDS);
+    words
+}
+
+// ld <register>, <immediate>;
+fn ldi(condition: Condition, register: Register, immediate: u32) -> u32 {
+    condition.generate()
+        | CpuMnemonic::Ldi.generate()
+        | register.generate() << 16
+        | (immediate & 0xffff)
+}
+
+// <mnemonic>.<modifier> <register>, <immediate>;
+fn alu_immediate(
+    mnemonic: AluCpuMnemonic,
+    modifier: Option<AluModifier>,
+    register: Register,
+    immediate: u32,
+) -> u32 {
+    mnemonic.generate()
+        | modifier.map_or(0, |modifier| modifier.generate())
+        | AluOpFlags::Immediate.generate()
+        | register.generate() << 12
+        | register.generate() << 8
+        | (immediate & 0xff)
+}
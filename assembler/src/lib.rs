@@ -1,23 +1,34 @@
-use std::{fs, io, path::Path};
+use std::{collections::HashMap, fs, io, path::Path};
 
-use ariadne::{Cache, FnCache};
+use ariadne::{Cache, FnCache, Source};
 
+use assembly::Assembly;
+use ast::Ast;
 use error::Error;
 use internment::Intern;
+use object::Object;
 use span::{Span, Spanned};
 use src::Src;
+use target::Target;
 
 use crate::error::{RecoveredError, RecoveredResult};
 
+pub mod assembly;
 mod ast;
+pub mod disassembler;
+pub mod emulator;
 pub mod error;
 mod expression;
 mod file;
 mod generator;
+pub mod object;
+pub mod output;
 mod parser;
 pub mod span;
 pub mod src;
 mod symbol_table;
+pub mod target;
+pub mod trace;
 mod utils;
 
 pub type SrcCache = FnCache<Intern<Src>, fn(&Intern<Src>) -> io::Result<String>, String>;
@@ -27,10 +38,114 @@ pub type SrcCache = FnCache<Intern<Src>, fn(&Intern<Src>) -> io::Result<String>,
 #[allow(clippy::type_complexity)] // the return type is not THAT bad
 pub fn assemble(
     src: &str,
-) -> RecoveredResult<Vec<u32>, Vec<u32>, (Vec<Error>, impl Cache<Intern<Src>>)> {
+    target: &Target,
+) -> RecoveredResult<Vec<u32>, Vec<u32>, (Vec<Error>, impl Cache<Intern<Src>> + use<>)> {
+    assemble_with_info(src, target).map(|assembly| assembly.machine_code)
+}
+
+// same as assemble, but also returns where each word came from in the source
+#[allow(clippy::type_complexity)]
+pub fn assemble_with_info(
+    src: &str,
+    target: &Target,
+) -> RecoveredResult<Assembly, Vec<u32>, (Vec<Error>, SrcCache)> {
+    assemble_checked(src, target, true)
+}
+
+// same as assemble, but the words and the addresses of ld instructions are not checked against the
+// memories of the target; used to reassemble a disassembly, where any word (e.g. data) may decode
+// as an ld of an address that isn't mapped
+#[allow(clippy::type_complexity)]
+pub fn assemble_unchecked(
+    src: &str,
+    target: &Target,
+) -> RecoveredResult<Vec<u32>, Vec<u32>, (Vec<Error>, impl Cache<Intern<Src>> + use<>)> {
+    assemble_checked(src, target, false).map(|assembly| assembly.machine_code)
+}
+
+#[allow(clippy::type_complexity)]
+fn assemble_checked(
+    src: &str,
+    target: &Target,
+    checked: bool,
+) -> RecoveredResult<Assembly, Vec<u32>, (Vec<Error>, SrcCache)> {
+    let (ast, mut errors, cache) = match init_ast(src, target, false) {
+        Ok(init) => init,
+        Err(errors) => return Err(RecoveredError(Vec::new(), errors)),
+    };
+
+    let (image, statements) = match ast.generate(target) {
+        Ok(generated) => generated,
+        Err(RecoveredError(generated, mut generation_errors)) => {
+            errors.append(&mut generation_errors);
+            generated
+        }
+    };
+
+    if checked {
+        errors.append(&mut ast.check(&image, &statements, target));
+    }
+
+    if errors.is_empty() {
+        Ok(Assembly {
+            target: target.clone(),
+            machine_code: image.rom.words,
+            ram: image.ram,
+            load_image: image.has_startup.then_some(image.load_image),
+            statements,
+            symbols: ast.assembled_symbols(),
+            cache,
+        })
+    } else {
+        Err(RecoveredError(image.rom.words, (errors, cache)))
+    }
+}
+
+// assembles only the given file, leaving the symbols it imports from other files to the linker
+// error includes recovered object
+#[allow(clippy::type_complexity, clippy::result_large_err)]
+pub fn assemble_object(
+    src: &str,
+    target: &Target,
+) -> RecoveredResult<Object, Object, (Vec<Error>, SrcCache)> {
+    let (ast, mut errors, cache) = match init_ast(src, target, true) {
+        Ok(init) => init,
+        Err(errors) => return Err(RecoveredError(Object::default(), errors)),
+    };
+
+    let mut object = match ast.generate_object(target) {
+        Ok(object) => object,
+        Err(RecoveredError(object, mut generation_errors)) => {
+            errors.append(&mut generation_errors);
+            object
+        }
+    };
+    object.name = src.to_string();
+
+    if errors.is_empty() {
+        Ok(object)
+    } else {
+        Err(RecoveredError(object, (errors, cache)))
+    }
+}
+
+// parses the root file and the files it imports, and fills the symbol tables; returns the errors so
+// far along with the ast, so that generating can still find more errors
+#[allow(clippy::type_complexity)]
+fn init_ast(
+    src: &str,
+    target: &Target,
+    object: bool,
+) -> Result<(Ast, Vec<Error>, SrcCache), (Vec<Error>, SrcCache)> {
     // cache of file name and corresponding file contents, used to
     // associate file names to contents for printing errors
-    let mut cache: SrcCache = FnCache::new(|src: &Intern<Src>| fs::read_to_string(src.as_path()));
+    let cache: SrcCache = FnCache::new(|src: &Intern<Src>| fs::read_to_string(src.as_path()));
+
+    // the target module is generated rather than read, so it is put in the cache up front
+    let mut cache = cache.with_sources(HashMap::from([(
+        Intern::new(Src::target_module()),
+        Source::from(target.module()),
+    )]));
 
     // fails if file not found
     // this is the root file, given in the command-line argument
@@ -41,7 +156,7 @@ pub fn assemble(
             Ok(src) => src,
             Err(error) => {
                 // have to do ths in a match instead of map_err because cache is moved
-                return Err(RecoveredError(Vec::new(), (vec![error], cache)));
+                return Err((vec![error], cache));
             }
         },
     );
@@ -53,7 +168,7 @@ pub fn assemble(
 
     let mut errors = Vec::new();
 
-    let mut ast = match src.build_ast(&mut cache) {
+    let mut ast = match src.build_ast(&mut cache, object) {
         Ok(ast) => ast,
         Err(RecoveredError(ast, mut file_errors)) => {
             errors.append(&mut file_errors);
@@ -61,22 +176,10 @@ pub fn assemble(
         }
     };
 
-    match ast.init_symbol_tables() {
+    match ast.init_symbol_tables(target) {
         Ok(_) => (),
         Err(mut symbol_table_errors) => errors.append(&mut symbol_table_errors),
     }
 
-    let machine_code = match ast.generate() {
-        Ok(machine_code) => machine_code,
-        Err(RecoveredError(machine_code, mut generation_errors)) => {
-            errors.append(&mut generation_errors);
-            machine_code
-        }
-    };
-
-    if errors.is_empty() {
-        Ok(machine_code)
-    } else {
-        Err(RecoveredError(machine_code, (errors, cache)))
-    }
+    Ok((ast, errors, cache))
 }
@@ -1,45 +1,175 @@
-use ablomm_asm::error::RecoveredError;
-use clap::Parser;
+use ablomm_asm::assembly::Assembly;
+use ablomm_asm::error::{Error, RecoveredError};
+use ablomm_asm::object::Object;
+use ablomm_asm::output::{self, Endianness, Segment};
+use ablomm_asm::target::Target;
+use clap::{Parser, ValueEnum};
 use std::io::{self, Write};
-use std::{fs, process::ExitCode};
+use std::{fs, path::Path, process::ExitCode};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// file input
-    input: String,
+    /// file input; with --link, the objects to link, in the order they are placed
+    #[arg(required = true)]
+    input: Vec<String>,
+
+    /// assemble the input into an object, without the files it imports, to be linked later
+    #[arg(short = 'c', long, conflicts_with_all = ["link", "format", "endianness", "ram_output", "depth"])]
+    object: bool,
+
+    /// link objects assembled with --object into machine code
+    #[arg(short, long)]
+    link: bool,
 
     /// file output
     #[arg(short, long)]
     output: Option<String>,
+
+    /// format of the output
+    #[arg(short, long, value_enum, default_value_t = Format::Hex)]
+    format: Format,
+
+    /// byte order of each word, for binary output formats
+    #[arg(long, value_enum, default_value_t = Endianness::Little)]
+    endianness: Endianness,
+
+    /// file output for a listing of each statement with the words it generated
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    listing: Option<String>,
+
+    /// file output for a map of every label and assignment
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    map: Option<String>,
+
+    /// file output for JSON debug information that maps each word back to the source
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    debug_info: Option<String>,
+
+    /// file output for exported symbols as a SystemVerilog package, named after the file
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    sv_package: Option<String>,
+
+    /// file output for exported symbols as a C header
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    c_header: Option<String>,
+
+    /// file output for exported symbols as Rust constants
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    rust_constants: Option<String>,
+
+    /// print the number of words used by each file, top-level block, and exported label
+    #[arg(long, conflicts_with_all = ["object", "link"])]
+    size_report: bool,
+
+    /// file output for the contents of the RAM, for formats that write it separately
+    #[arg(long)]
+    ram_output: Option<String>,
+
+    /// number of words in the memory, for memory initialization formats [default: the depth of the
+    /// ROM of the target]
+    #[arg(long)]
+    depth: Option<usize>,
+
+    /// TOML file describing the ROM, RAM, and devices of the machine [default: the simulator]
+    #[arg(short, long)]
+    target: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// one word per line as 8 hex digits; the format read by the simulator
+    Hex,
+
+    /// raw 32-bit words
+    Bin,
+
+    /// Intel HEX, with byte addresses (word address * 4)
+    Ihex,
+
+    /// Motorola S-record, with byte addresses (word address * 4)
+    Srec,
+
+    /// Altera memory initialization file, padded to the memory depth
+    Mif,
+
+    /// Xilinx coefficient file, padded to the memory depth
+    Coe,
+
+    /// verilog hex with @address records, as read by $readmemh; the RAM is written to --ram-output
+    Vmem,
 }
 
 fn main() -> ExitCode {
     let args = Args::parse();
 
-    match ablomm_asm::assemble(&args.input) {
-        Ok(machine_code) => {
-            let machine_code = machine_code_to_string(&machine_code);
-            match &args.output {
-                Some(output_file) => match fs::write(output_file, machine_code) {
-                    Ok(_) => (),
-                    Err(error) => {
-                        let _ = writeln!(
-                            io::stderr(),
-                            "Error while writing to file \"{}\": {}",
-                            output_file,
-                            error
-                        );
-
-                        return ExitCode::FAILURE;
-                    }
-                },
-                None => {
-                    print!("{}", machine_code); // panics for io errors
-                }
+    let target = match &args.target {
+        Some(target_file) => {
+            let Some(target) = read_target(target_file) else {
+                return ExitCode::FAILURE;
+            };
+            target
+        }
+        None => Target::default(),
+    };
+
+    if args.object {
+        return assemble_objects(&args, &target);
+    }
+
+    if args.link {
+        return link_objects(&args, &target);
+    }
+
+    let [input] = args.input.as_slice() else {
+        let _ = writeln!(
+            io::stderr(),
+            "Only one file can be assembled at a time; try assembling each file with --object, then linking them with --link"
+        );
+        return ExitCode::FAILURE;
+    };
+
+    match ablomm_asm::assemble_with_info(input, &target) {
+        Ok(mut assembly) => {
+            if let Some(listing_file) = &args.listing
+                && !write_output(
+                    Some(listing_file),
+                    output::to_listing(&mut assembly).as_bytes(),
+                )
+            {
+                return ExitCode::FAILURE;
+            }
+
+            if let Some(map_file) = &args.map
+                && !write_output(Some(map_file), output::to_map(&mut assembly).as_bytes())
+            {
+                return ExitCode::FAILURE;
+            }
+
+            if let Some(debug_info_file) = &args.debug_info
+                && !write_output(
+                    Some(debug_info_file),
+                    output::to_debug_info(&mut assembly).as_bytes(),
+                )
+            {
+                return ExitCode::FAILURE;
             }
 
-            ExitCode::SUCCESS
+            if args.size_report {
+                eprint!("{}", output::to_size_report(&assembly));
+            }
+
+            if !write_constants(&mut assembly, &args) {
+                return ExitCode::FAILURE;
+            }
+
+            write_machine_code(
+                &assembly.target,
+                &assembly.machine_code,
+                &assembly.ram,
+                assembly.load_image.is_some(),
+                &args,
+            )
         }
         Err(RecoveredError(_, (errors, mut cache))) => {
             for error in errors {
@@ -51,11 +181,255 @@ fn main() -> ExitCode {
     }
 }
 
-fn machine_code_to_string(machine_code: &Vec<u32>) -> String {
-    let mut machine_code_string = String::with_capacity(machine_code.len() * 8);
-    for opcode in machine_code {
-        machine_code_string.push_str(&format!("{:0>8x}\n", opcode));
+// each input is assembled into an object; the output can only be given for a single input, as
+// otherwise each object is written next to its input (e.g. lib/print.asm to lib/print.o)
+fn assemble_objects(args: &Args, target: &Target) -> ExitCode {
+    if args.output.is_some() && args.input.len() > 1 {
+        let _ = writeln!(
+            io::stderr(),
+            "An output can only be given when assembling a single object; without it, each object is written next to its input"
+        );
+        return ExitCode::FAILURE;
+    }
+
+    for input in &args.input {
+        match ablomm_asm::assemble_object(input, target) {
+            Ok(object) => {
+                let output = match &args.output {
+                    Some(output) => output.clone(),
+                    None => Path::new(input).with_extension("o").display().to_string(),
+                };
+
+                if !write_output(Some(&output), object.to_toml().as_bytes()) {
+                    return ExitCode::FAILURE;
+                }
+            }
+            Err(RecoveredError(_, (errors, mut cache))) => {
+                for error in errors {
+                    let _ = error.eprint(&mut cache);
+                }
+
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn link_objects(args: &Args, target: &Target) -> ExitCode {
+    let mut objects = Vec::with_capacity(args.input.len());
+    for input in &args.input {
+        let object = match fs::read_to_string(input) {
+            Ok(object) => object,
+            Err(error) => {
+                let _ = writeln!(
+                    io::stderr(),
+                    "Error while reading file \"{}\": {}",
+                    input,
+                    error
+                );
+
+                return ExitCode::FAILURE;
+            }
+        };
+
+        match Object::parse(&object, input) {
+            Ok(object) => objects.push(object),
+            Err(error) => {
+                let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match ablomm_asm::object::link(&objects, target) {
+        Ok(linked) => write_machine_code(
+            &linked.target,
+            &linked.machine_code,
+            &linked.ram,
+            linked.load_image.is_some(),
+            args,
+        ),
+        Err(errors) => {
+            for error in errors {
+                let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+            }
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// writes the machine code to the output, and the RAM to the RAM output for formats that write it
+// separately
+fn write_machine_code(
+    target: &Target,
+    machine_code: &[u32],
+    ram: &[Segment],
+    has_load_image: bool,
+    args: &Args,
+) -> ExitCode {
+    let (machine_code, ram) =
+        match format_machine_code(target, machine_code, ram, has_load_image, args) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+                return ExitCode::FAILURE;
+            }
+        };
+
+    if !write_output(args.output.as_ref(), &machine_code) {
+        return ExitCode::FAILURE;
+    }
+
+    if let Some(ram) = ram
+        && !write_output(args.ram_output.as_ref(), &ram)
+    {
+        return ExitCode::FAILURE;
     }
 
-    machine_code_string
+    ExitCode::SUCCESS
+}
+
+// returns the formatted machine code, and the contents of the RAM for formats that write it to a
+// separate file
+fn format_machine_code(
+    target: &Target,
+    machine_code: &[u32],
+    ram: &[Segment],
+    has_load_image: bool,
+    args: &Args,
+) -> Result<(Vec<u8>, Option<Vec<u8>>), Error> {
+    let depth = args.depth.unwrap_or(target.rom.depth as usize);
+
+    // only vmem has a separate file for the RAM, but the RAM doesn't need to be initialized if the
+    // startup code copies the load image to it
+    let needs_ram = !ram.is_empty() && !has_load_image;
+    if needs_ram && args.format != Format::Vmem {
+        return Err(Error::Bare(
+            "There is initialized data in RAM, which can only be written with --format vmem and --ram-output <FILE>, or copied to RAM with a startup directive"
+                .to_string(),
+        ));
+    }
+
+    let formatted = match args.format {
+        Format::Hex => output::to_hex(machine_code).into_bytes(),
+        Format::Bin => output::to_bin(machine_code, args.endianness),
+        Format::Ihex => {
+            output::to_ihex(machine_code, target.rom.start, args.endianness).into_bytes()
+        }
+        Format::Srec => {
+            output::to_srec(machine_code, target.rom.start, args.endianness).into_bytes()
+        }
+        Format::Mif => output::to_mif(machine_code, depth)?.into_bytes(),
+        Format::Coe => output::to_coe(machine_code, depth)?.into_bytes(),
+        Format::Vmem => {
+            let mut segments = vec![Segment {
+                address: target.rom.start,
+                words: machine_code.to_vec(),
+            }];
+            if needs_ram || args.ram_output.is_some() {
+                segments.extend(ram.iter().cloned());
+            }
+
+            let vmem = output::to_vmem(&segments, target)?;
+            if vmem.ram.is_some() && args.ram_output.is_none() {
+                return Err(Error::Bare(
+                    "Some of the machine code is placed in RAM, but there is no --ram-output <FILE>"
+                        .to_string(),
+                ));
+            }
+
+            return Ok((vmem.rom.into_bytes(), vmem.ram.map(String::into_bytes)));
+        }
+    };
+
+    Ok((formatted, None))
+}
+
+// writes the exported symbols to each of the constants files given; returns false if any failed
+fn write_constants(assembly: &mut Assembly, args: &Args) -> bool {
+    let mut outputs = Vec::new();
+
+    if let Some(file) = &args.sv_package {
+        let package_name = output::identifier_from_path(file);
+        outputs.push((file, output::to_sv_package(assembly, &package_name)));
+    }
+
+    if let Some(file) = &args.c_header {
+        let guard_name = output::identifier_from_path(file).to_uppercase() + "_H";
+        outputs.push((file, output::to_c_header(assembly, &guard_name)));
+    }
+
+    if let Some(file) = &args.rust_constants {
+        outputs.push((file, output::to_rust_constants(assembly)));
+    }
+
+    for (file, constants) in outputs {
+        match constants {
+            Ok(constants) => {
+                if !write_output(Some(file), constants.as_bytes()) {
+                    return false;
+                }
+            }
+            Err(error) => {
+                let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+// returns none if the target file could not be read or is invalid, after printing the error
+fn read_target(target_file: &str) -> Option<Target> {
+    let target = match fs::read_to_string(target_file) {
+        Ok(target) => target,
+        Err(error) => {
+            let _ = writeln!(
+                io::stderr(),
+                "Error while reading file \"{}\": {}",
+                target_file,
+                error
+            );
+
+            return None;
+        }
+    };
+
+    match Target::parse(&target) {
+        Ok(target) => Some(target),
+        Err(error) => {
+            let _ = error.eprint(ariadne::sources(Vec::<(_, String)>::new()));
+            None
+        }
+    }
+}
+
+// writes to stdout if there is no output file; returns false if it could not be written
+fn write_output(output_file: Option<&String>, contents: &[u8]) -> bool {
+    match output_file {
+        Some(output_file) => match fs::write(output_file, contents) {
+            Ok(_) => true,
+            Err(error) => {
+                let _ = writeln!(
+                    io::stderr(),
+                    "Error while writing to file \"{}\": {}",
+                    output_file,
+                    error
+                );
+
+                false
+            }
+        },
+        None => {
+            io::stdout()
+                .write_all(contents)
+                .expect("Error writing to stdout");
+
+            true
+        }
+    }
 }
@@ -0,0 +1,207 @@
+// an object is a single file assembled without the files it imports, so that libraries can be
+// assembled once and linked into many programs
+// the words of an object are assembled as if the object was at address 0 of the ROM and RAM, and
+// the linker patches every word that depends on where the object (or a symbol it imports) is placed
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, output::Segment, utils::is_identifier};
+
+mod link;
+
+pub use link::{Linked, link};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Object {
+    // the file the object was assembled from, used in linker errors
+    pub name: String,
+
+    // the text and rodata sections
+    pub rom: Vec<u32>,
+
+    // the initialized parts of the data section, and the number of words in RAM including the bss
+    // section and any other reserved words
+    #[serde(default)]
+    pub ram: Vec<Segment>,
+    #[serde(default)]
+    pub ram_depth: u32,
+
+    // the object must be placed at a multiple of these, so that its align directives still hold
+    #[serde(default = "default_alignment")]
+    pub rom_alignment: u32,
+    #[serde(default = "default_alignment")]
+    pub ram_alignment: u32,
+
+    // the startup code is generated by the linker, as it needs the load image of every object
+    #[serde(default)]
+    pub startup: Option<Startup>,
+
+    #[serde(default)]
+    pub exports: Vec<Export>,
+
+    #[serde(default)]
+    pub relocations: Vec<Relocation>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Memory {
+    Rom,
+    Ram,
+}
+
+// what a value is relative to, which is only known once the linker places the objects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Base {
+    Rom,            // the start of the object in ROM
+    Ram,            // the start of the object in RAM
+    Symbol(String), // a symbol exported by any of the linked objects
+}
+
+// a number, which is the address of the base plus the offset if there is a base
+// values are written like expressions, e.g. `0x12`, `.rom + 0x4`, or `print + 0x0`; the names of
+// the object's memories start with a dot so they can't be confused with a symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Value {
+    pub base: Option<Base>,
+    pub offset: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Export {
+    pub name: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Startup {
+    // the address of the startup words, relative to the start of the object in ROM
+    pub offset: u32,
+    pub entry: Value,
+}
+
+// a word with a field that is set to a value by the linker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Relocation {
+    // the address of the word, relative to the start of the object in the memory
+    pub memory: Memory,
+    pub offset: u32,
+
+    pub kind: RelocationKind,
+    pub value: Value,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelocationKind {
+    Address,      // the 16-bit address of a ld or st, e.g. `ld r0, *label;`
+    Immediate,    // the 16-bit immediate of a ldi, e.g. `ld r0, label;`
+    AluImmediate, // the 8-bit immediate of an alu operation, e.g. `add r0, label;`
+    Word,         // a whole word, e.g. `label;`
+}
+
+impl RelocationKind {
+    // the bits of the word that are set by the linker
+    pub fn mask(&self) -> u32 {
+        match self {
+            RelocationKind::Address | RelocationKind::Immediate => 0xffff,
+            RelocationKind::AluImmediate => 0xff,
+            RelocationKind::Word => 0xffff_ffff,
+        }
+    }
+}
+
+impl Display for RelocationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelocationKind::Address => write!(f, "16-bit address"),
+            RelocationKind::Immediate => write!(f, "16-bit immediate"),
+            RelocationKind::AluImmediate => write!(f, "8-bit alu immediate"),
+            RelocationKind::Word => write!(f, "word"),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.base {
+            None => write!(f, "0x{:x}", self.offset),
+            Some(Base::Rom) => write!(f, ".rom + 0x{:x}", self.offset),
+            Some(Base::Ram) => write!(f, ".ram + 0x{:x}", self.offset),
+            Some(Base::Symbol(symbol)) => write!(f, "{} + 0x{:x}", symbol, self.offset),
+        }
+    }
+}
+
+impl From<Value> for String {
+    fn from(value: Value) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for Value {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (base, offset) = match value.rsplit_once('+') {
+            Some((base, offset)) => (Some(base.trim()), offset.trim()),
+            None => (None, value.trim()),
+        };
+
+        let offset = match offset.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => offset.parse(),
+        }
+        .map_err(|error| format!("Invalid offset in \"{}\": {}", value, error))?;
+
+        let base = match base {
+            None => None,
+            Some(".rom") => Some(Base::Rom),
+            Some(".ram") => Some(Base::Ram),
+            Some(symbol) if is_identifier(symbol) => Some(Base::Symbol(symbol.to_string())),
+            Some(base) => return Err(format!("Invalid base \"{}\" in \"{}\"", base, value)),
+        };
+
+        Ok(Value { base, offset })
+    }
+}
+
+fn default_alignment() -> u32 {
+    1
+}
+
+impl Default for Object {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            rom: Vec::new(),
+            ram: Vec::new(),
+            ram_depth: 0,
+            rom_alignment: default_alignment(),
+            ram_alignment: default_alignment(),
+            startup: None,
+            exports: Vec::new(),
+            relocations: Vec::new(),
+        }
+    }
+}
+
+impl Object {
+    // file is only used in the error message
+    pub fn parse(object: &str, file: &str) -> Result<Self, Error> {
+        toml::from_str(object)
+            .map_err(|error| Error::Bare(format!("Invalid object \"{}\": {}", file, error)))
+    }
+
+    pub fn to_toml(&self) -> String {
+        // should never panic because every field of an object can be written as TOML
+        toml::to_string(self).expect("Object could not be written as TOML")
+    }
+}
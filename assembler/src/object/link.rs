@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use crate::{
+    assembly::LoadImage,
+    error::Error,
+    generator::{Image, STARTUP_WORDS, startup_words},
+    object::{Base, Memory, Object, Relocation, Value},
+    output::{Region, Segment},
+    target::Target,
+};
+
+// the machine code of the linked objects, in the same form as an assembled program
+pub struct Linked {
+    pub target: Target,
+
+    // the contents of the ROM, starting at the start of the ROM
+    pub machine_code: Vec<u32>,
+
+    // the initialized parts of the RAM
+    pub ram: Vec<Segment>,
+
+    // where the initial values of the RAM are stored in ROM, if an object has a startup directive
+    pub load_image: Option<LoadImage>,
+}
+
+// the start of an object in each memory
+#[derive(Debug, Copy, Clone)]
+struct Placement {
+    rom: u32,
+    ram: u32,
+}
+
+struct Linker<'a> {
+    objects: &'a [Object],
+    placements: Vec<Placement>,
+
+    // each exported symbol, and the index of the object that exports it
+    symbols: HashMap<&'a str, (usize, &'a Value)>,
+}
+
+// places the objects one after another in the order given, then sets every relocation to the
+// final address it refers to
+pub fn link(objects: &[Object], target: &Target) -> Result<Linked, Vec<Error>> {
+    let linker = Linker {
+        objects,
+        placements: place(objects, target).map_err(|error| vec![error])?,
+        symbols: exported_symbols(objects)?,
+    };
+
+    let mut image = Image::new(target, LoadImage::default());
+    let mut errors = Vec::new();
+
+    for (object, placement) in objects.iter().zip(&linker.placements) {
+        // any addresses skipped over to align the object are filled with 0
+        let index = (placement.rom - target.rom.start) as usize;
+        image.rom.words.resize(index, 0);
+        image.rom.words.extend_from_slice(&object.rom);
+
+        // segments that follow each other are merged, the same as when assembling
+        for segment in &object.ram {
+            let address = placement.ram + segment.address;
+            match image.ram.last_mut() {
+                Some(last) if last.end() == Some(address) => {
+                    last.words.extend_from_slice(&segment.words)
+                }
+                _ => image.ram.push(Segment {
+                    address,
+                    words: segment.words.clone(),
+                }),
+            }
+        }
+    }
+
+    for (index, object) in objects.iter().enumerate() {
+        for relocation in &object.relocations {
+            if let Err(error) = linker.relocate(&mut image, index, relocation) {
+                errors.push(error);
+            }
+        }
+    }
+
+    if let Err(error) = linker.startup(&mut image, target) {
+        errors.push(error);
+    }
+
+    if errors.is_empty() {
+        Ok(Linked {
+            target: target.clone(),
+            machine_code: image.rom.words,
+            ram: image.ram,
+            load_image: image.has_startup.then_some(image.load_image),
+        })
+    } else {
+        Err(errors)
+    }
+}
+
+// each object is placed after the previous one, at a multiple of its alignment
+fn place(objects: &[Object], target: &Target) -> Result<Vec<Placement>, Error> {
+    let mut placements = Vec::with_capacity(objects.len());
+    let (mut rom, mut ram) = (target.rom.start, target.ram.start);
+
+    for object in objects {
+        let placement = Placement {
+            rom: rom.next_multiple_of(object.rom_alignment.max(1)),
+            ram: ram.next_multiple_of(object.ram_alignment.max(1)),
+        };
+
+        let rom_words = Region {
+            start: placement.rom,
+            depth: object.rom.len() as u32,
+        };
+        let ram_words = Region {
+            start: placement.ram,
+            depth: object.ram_depth,
+        };
+
+        for (memory, words, region) in [
+            ("ROM", rom_words, target.rom),
+            ("RAM", ram_words, target.ram),
+        ] {
+            if !region.contains_region(&words) {
+                return Err(Error::Bare(format!(
+                    "The {} words of \"{}\" would be placed at 0x{:04x} with a depth of 0x{:x}, but the {} of the target \"{}\" is {}",
+                    memory, object.name, words.start, words.depth, memory, target.name, region
+                )));
+            }
+        }
+
+        rom = rom_words.end();
+        ram = ram_words.end();
+        placements.push(placement);
+    }
+
+    Ok(placements)
+}
+
+fn exported_symbols(objects: &[Object]) -> Result<HashMap<&str, (usize, &Value)>, Vec<Error>> {
+    let mut symbols = HashMap::new();
+    let mut errors = Vec::new();
+
+    for (index, object) in objects.iter().enumerate() {
+        for export in &object.exports {
+            if let Some((other, _)) = symbols.insert(export.name.as_str(), (index, &export.value)) {
+                errors.push(Error::Bare(format!(
+                    "The symbol \"{}\" is exported by both \"{}\" and \"{}\"",
+                    export.name, objects[other].name, object.name
+                )));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(symbols)
+    } else {
+        Err(errors)
+    }
+}
+
+impl Linker<'_> {
+    // the final value of a value in an object; resolving is the symbols being resolved, in order to
+    // detect symbols that are exported as each other
+    fn resolve(
+        &self,
+        value: &Value,
+        index: usize,
+        resolving: &mut Vec<String>,
+    ) -> Result<u32, Error> {
+        let base = match &value.base {
+            None => 0,
+            Some(Base::Rom) => self.placements[index].rom,
+            Some(Base::Ram) => self.placements[index].ram,
+            Some(Base::Symbol(symbol)) => {
+                let Some((export_index, export)) = self.symbols.get(symbol.as_str()) else {
+                    return Err(Error::Bare(format!(
+                        "The symbol \"{}\" imported by \"{}\" is not exported by any of the objects",
+                        symbol, self.objects[index].name
+                    )));
+                };
+
+                if resolving.contains(symbol) {
+                    return Err(Error::Bare(format!(
+                        "The symbol \"{}\" is exported as itself through \"{}\"",
+                        symbol,
+                        resolving.join("\", \"")
+                    )));
+                }
+
+                resolving.push(symbol.clone());
+                let address = self.resolve(export, *export_index, resolving)?;
+                resolving.pop();
+                address
+            }
+        };
+
+        Ok(base.wrapping_add(value.offset))
+    }
+
+    // sets the field of the word to the final value
+    fn relocate(
+        &self,
+        image: &mut Image,
+        index: usize,
+        relocation: &Relocation,
+    ) -> Result<(), Error> {
+        let object = &self.objects[index];
+        let placement = self.placements[index];
+
+        let value = self.resolve(&relocation.value, index, &mut Vec::new())?;
+        let address = match relocation.memory {
+            Memory::Rom => placement.rom,
+            Memory::Ram => placement.ram,
+        } + relocation.offset;
+
+        let mask = relocation.kind.mask();
+        if value & !mask != 0 {
+            return Err(Error::Bare(format!(
+                "The {} of the word at 0x{:04x} in \"{}\" is 0x{:x}, which does not fit in it",
+                relocation.kind, address, object.name, value
+            )));
+        }
+
+        let word = match relocation.memory {
+            Memory::Rom => image
+                .rom
+                .words
+                .get_mut((address - image.rom.address) as usize),
+            Memory::Ram => image
+                .ram
+                .iter_mut()
+                .find(|segment| {
+                    segment.address <= address && segment.end().is_some_and(|end| address < end)
+                })
+                .map(|segment| &mut segment.words[(address - segment.address) as usize]),
+        };
+
+        let Some(word) = word else {
+            return Err(Error::Bare(format!(
+                "The relocation of the word at 0x{:04x} in \"{}\" is not in the words of the object",
+                address, object.name
+            )));
+        };
+
+        *word = (*word & !mask) | value;
+        Ok(())
+    }
+
+    // generates the startup words of the object with a startup directive, and places the load image
+    // after all the other words in ROM
+    fn startup(&self, image: &mut Image, target: &Target) -> Result<(), Error> {
+        let mut startups = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, object)| object.startup.as_ref().map(|startup| (index, startup)));
+
+        let Some((index, startup)) = startups.next() else {
+            return Ok(());
+        };
+
+        if let Some((other, _)) = startups.next() {
+            return Err(Error::Bare(format!(
+                "Both \"{}\" and \"{}\" have a startup directive, but only one can be linked",
+                self.objects[index].name, self.objects[other].name
+            )));
+        }
+
+        let entry = self.resolve(&startup.entry, index, &mut Vec::new())?;
+        if entry >= 1 << 16 {
+            return Err(Error::Bare(format!(
+                "The entry of the startup directive in \"{}\" is 0x{:x}, which is outside of the address space",
+                self.objects[index].name, entry
+            )));
+        }
+
+        let data_start = image.ram.iter().map(|segment| segment.address).min();
+        let data_end = image.ram.iter().filter_map(Segment::end).max();
+        image.load_image = LoadImage {
+            address: image.rom.end().expect("The linked objects are in the ROM"),
+            data: data_start.unwrap_or(target.ram.start)..data_end.unwrap_or(target.ram.start),
+        };
+        image.has_startup = true;
+
+        let address = self.placements[index].rom + startup.offset;
+        let words = startup_words(entry, address, &image.load_image);
+        let start = (address - image.rom.address) as usize;
+        let Some(startup_words) = image
+            .rom
+            .words
+            .get_mut(start..start + STARTUP_WORDS as usize)
+        else {
+            return Err(Error::Bare(format!(
+                "The startup directive in \"{}\" is not in the words of the object",
+                self.objects[index].name
+            )));
+        };
+        startup_words.copy_from_slice(&words);
+
+        let load_image = image.load_image_words();
+        image.rom.words.extend(load_image);
+
+        let rom_words = Region {
+            start: image.rom.address,
+            depth: image.rom.words.len() as u32,
+        };
+        if !target.rom.contains_region(&rom_words) {
+            return Err(Error::Bare(format!(
+                "The load image of the linked objects is at 0x{:04x} to 0x{:04x}, but the ROM of the target \"{}\" is {}",
+                image.load_image.address,
+                rom_words.end() - 1,
+                target.name,
+                target.rom
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+// the different file formats the machine code can be written as
+// the CPU is word addressable, but most of these formats are byte addressable, so for those formats
+// the byte address of a word is the word's address * 4
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+mod bin;
+mod coe;
+mod constants;
+mod debug_info;
+mod hex;
+mod ihex;
+mod listing;
+mod map;
+mod mif;
+mod size_report;
+mod srec;
+mod vmem;
+
+pub use bin::{Endianness, to_bin};
+pub use coe::to_coe;
+pub use constants::{identifier_from_path, to_c_header, to_rust_constants, to_sv_package};
+pub use debug_info::{
+    DebugInfo, Location, ScopeInfo, SymbolInfo, WordInfo, parse_debug_info, to_debug_info,
+};
+pub use hex::to_hex;
+pub use ihex::to_ihex;
+pub use listing::to_listing;
+pub(crate) use map::MAP_HEADER;
+pub use map::to_map;
+pub use mif::to_mif;
+pub use size_report::to_size_report;
+pub use srec::to_srec;
+pub use vmem::{Vmem, to_vmem};
+
+// a span of word addresses
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    pub start: u32,
+    pub depth: u32,
+}
+
+impl Region {
+    pub fn end(&self) -> u32 {
+        self.start + self.depth
+    }
+
+    pub fn contains(&self, segment: &Segment) -> bool {
+        segment.address >= self.start && segment.end().is_some_and(|end| end <= self.end())
+    }
+
+    pub fn contains_region(&self, region: &Region) -> bool {
+        region.start >= self.start
+            && region
+                .start
+                .checked_add(region.depth)
+                .is_some_and(|end| end <= self.end())
+    }
+
+    pub fn overlaps(&self, region: &Region) -> bool {
+        self.start < region.end() && region.start < self.end()
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:04x} to 0x{:04x}", self.start, self.end() - 1)
+    }
+}
+
+// contiguous machine code starting at an address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Segment {
+    pub address: u32,
+    pub words: Vec<u32>,
+}
+
+impl Segment {
+    // the address after the last word; None if it is past the end of the address space, which can
+    // happen for segments that are read from files
+    pub fn end(&self) -> Option<u32> {
+        self.address.checked_add(self.words.len() as u32)
+    }
+}
+
+// the addresses of the words, which may be past the end of the address space
+impl Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let last = self.address as u64 + (self.words.len() as u64).max(1) - 1;
+        write!(f, "0x{:04x} to 0x{:04x}", self.address, last)
+    }
+}
+
+// memory initialization formats describe the whole memory, so the machine code has to fit in it
+fn check_depth(machine_code: &[u32], depth: usize) -> Result<(), Error> {
+    if depth == 0 || machine_code.len() > depth {
+        return Err(Error::Bare(format!(
+            "The machine code is {} words, which does not fit in a memory of depth {}",
+            machine_code.len(),
+            depth
+        )));
+    }
+
+    Ok(())
+}
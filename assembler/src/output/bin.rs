@@ -0,0 +1,20 @@
+use clap::ValueEnum;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+// raw 32-bit words with no padding or header
+pub fn to_bin(machine_code: &[u32], endianness: Endianness) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(machine_code.len() * 4);
+    for opcode in machine_code {
+        match endianness {
+            Endianness::Little => bytes.extend_from_slice(&opcode.to_le_bytes()),
+            Endianness::Big => bytes.extend_from_slice(&opcode.to_be_bytes()),
+        }
+    }
+
+    bytes
+}
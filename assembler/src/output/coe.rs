@@ -0,0 +1,21 @@
+use crate::error::Error;
+use crate::output::check_depth;
+
+// Xilinx coefficient file, with the words after the machine code set to 0 so that the whole memory
+// is initialized
+pub fn to_coe(machine_code: &[u32], depth: usize) -> Result<String, Error> {
+    check_depth(machine_code, depth)?;
+
+    let mut coe = String::new();
+    coe.push_str("memory_initialization_radix=16;\n");
+    coe.push_str("memory_initialization_vector=\n");
+
+    let padding = std::iter::repeat_n(&0, depth - machine_code.len());
+    for (address, opcode) in machine_code.iter().chain(padding).enumerate() {
+        // the vector is separated by commas and terminated by a semicolon
+        let separator = if address + 1 == depth { ';' } else { ',' };
+        coe.push_str(&format!("{:08x}{}\n", opcode, separator));
+    }
+
+    Ok(coe)
+}
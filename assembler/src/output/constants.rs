@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::assembly::{AssembledSymbol, Assembly, line_column};
+use crate::error::Error;
+
+// exported symbols as SystemVerilog, C, and Rust constants, so that other code (e.g. testbenches)
+// can refer to addresses without hard-coding them
+// only symbols with a number (addresses, numbers, and indirect numbers) are written, and the names
+// are uppercase (e.g. `print` is `PRINT`), which also avoids clashing with keywords such as `end`
+
+// e.g. `localparam logic [31:0] PRINT = 32'h00000014;  // print (address)`
+pub fn to_sv_package(assembly: &mut Assembly, package_name: &str) -> Result<String, Error> {
+    let constants = constants(assembly)?;
+
+    let mut package = String::new();
+    package.push_str(&format!("package {};\n", package_name));
+    for (name, symbol, number) in constants {
+        package.push_str(&format!(
+            "  localparam logic [31:0] {} = 32'h{:08x};  // {} ({})\n",
+            name, number, symbol.name, symbol.kind
+        ));
+    }
+    package.push_str("endpackage\n");
+
+    Ok(package)
+}
+
+// e.g. `#define PRINT 0x00000014u // print (address)`
+pub fn to_c_header(assembly: &mut Assembly, guard_name: &str) -> Result<String, Error> {
+    let constants = constants(assembly)?;
+
+    let mut header = String::new();
+    header.push_str(&format!("#ifndef {}\n", guard_name));
+    header.push_str(&format!("#define {}\n", guard_name));
+    header.push('\n');
+    for (name, symbol, number) in constants {
+        header.push_str(&format!(
+            "#define {} 0x{:08x}u // {} ({})\n",
+            name, number, symbol.name, symbol.kind
+        ));
+    }
+    header.push('\n');
+    header.push_str(&format!("#endif // {}\n", guard_name));
+
+    Ok(header)
+}
+
+// e.g. `pub const PRINT: u32 = 0x00000014; // print (address)`
+pub fn to_rust_constants(assembly: &mut Assembly) -> Result<String, Error> {
+    let constants = constants(assembly)?;
+
+    let mut rust = String::new();
+    for (name, symbol, number) in constants {
+        rust.push_str(&format!(
+            "pub const {}: u32 = 0x{:08x}; // {} ({})\n",
+            name, number, symbol.name, symbol.kind
+        ));
+    }
+
+    Ok(rust)
+}
+
+// the uppercase name, symbol, and number of each symbol with a number exported from a file
+// block exports are not included, as they only export the symbol to the enclosing block
+fn constants(assembly: &mut Assembly) -> Result<Vec<(String, &AssembledSymbol, u32)>, Error> {
+    let mut constants = Vec::new();
+    let mut names: HashMap<String, &AssembledSymbol> = HashMap::new();
+
+    let Assembly { symbols, cache, .. } = assembly;
+
+    for symbol in symbols.iter() {
+        let file_export = symbol.exported && symbol.scope.is_empty();
+        let Some(number) = symbol.number.filter(|_| file_export) else {
+            continue;
+        };
+
+        let name = symbol.name.to_uppercase();
+
+        // different files can export the same name, and names can differ only by case
+        if let Some(other) = names.get(&name) {
+            let mut location = |symbol: &AssembledSymbol| match line_column(cache, &symbol.span) {
+                Some((line, column)) => format!("{}:{}:{}", symbol.span.src, line, column),
+                None => symbol.span.src.to_string(),
+            };
+
+            return Err(Error::Bare(format!(
+                "Exported symbols \"{}\" ({}) and \"{}\" ({}) have the same constant name \"{}\"",
+                other.name,
+                location(other),
+                symbol.name,
+                location(symbol),
+                name
+            )));
+        }
+
+        names.insert(name.clone(), symbol);
+        constants.push((name, symbol, number));
+    }
+
+    Ok(constants)
+}
+
+// an identifier made from a file name, e.g. "build/symbols.sv" -> "symbols"
+pub fn identifier_from_path(path: &str) -> String {
+    let stem = std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    let identifier: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if identifier.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        identifier
+    } else {
+        format!("_{}", identifier)
+    }
+}
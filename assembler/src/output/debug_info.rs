@@ -0,0 +1,297 @@
+use std::fs;
+
+use ariadne::Cache;
+use internment::Intern;
+use serde::{Deserialize, Serialize};
+
+use crate::SrcCache;
+use crate::assembly::{Assembly, StatementKind, line_column};
+use crate::disassembler::Symbols;
+use crate::error::Error;
+use crate::span::Span;
+use crate::src::Src;
+
+// the debug information as written by `to_debug_info`; when it is read, fields that tools don't
+// know are ignored so that older tools can read newer debug information
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugInfo {
+    pub files: Vec<String>,
+    pub words: Vec<WordInfo>,
+
+    #[serde(default)]
+    pub scopes: Vec<ScopeInfo>,
+
+    #[serde(default)]
+    pub symbols: Vec<SymbolInfo>,
+
+    // the contents of each file, once read
+    #[serde(skip)]
+    sources: Vec<Option<String>>,
+}
+
+// a span of a file; "start" and "end" are byte offsets into the file, and lines and columns start
+// at 1
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub file: usize,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+// a word of the machine code, and the statement that generated it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordInfo {
+    pub address: u32,
+
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+// a whole file (depth 0), or the part of it in ROM or RAM, or a block (depth 1 and above), which is
+// named the same way as the scope of a symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopeInfo {
+    pub name: Option<String>,
+    pub start_address: u32,
+    pub end_address: u32,
+
+    #[serde(flatten)]
+    pub location: Location,
+
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub kind: String,
+    pub value: String,
+    pub number: Option<u32>,
+    pub exported: bool,
+    pub scope: Vec<String>,
+
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+impl DebugInfo {
+    // the statement that generated the word at the address
+    pub fn word(&self, address: u32) -> Option<&WordInfo> {
+        self.words.iter().find(|word| word.address == address)
+    }
+
+    pub fn file(&self, word: &WordInfo) -> Option<&str> {
+        self.files.get(word.location.file).map(String::as_str)
+    }
+
+    // reads the files, so the text of their lines can be shown; the paths are relative to where the
+    // assembler was run, and files that can't be read (e.g. the target) have no text
+    pub fn read_sources(&mut self) {
+        self.sources = self
+            .files
+            .iter()
+            .map(|file| fs::read_to_string(file).ok())
+            .collect();
+    }
+
+    // the line of the statement that generated the word, without indentation; None if the sources
+    // haven't been read
+    pub fn line(&self, word: &WordInfo) -> Option<&str> {
+        let source = self.sources.get(word.location.file)?.as_ref()?;
+        let line = source.lines().nth(word.location.line.checked_sub(1)?)?;
+        Some(line.trim())
+    }
+
+    // the labels of the program, for the disassembler
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+            .iter()
+            .filter(|symbol| symbol.kind == "address" || symbol.kind == "indirect")
+            .filter_map(|symbol| Some((symbol.number?, symbol.name.clone())))
+            .collect()
+    }
+}
+
+// parses the debug information written by `to_debug_info`
+pub fn parse_debug_info(debug_info: &str) -> Result<DebugInfo, Error> {
+    serde_json::from_str(debug_info)
+        .map_err(|error| Error::Bare(format!("Invalid debug information: {}", error)))
+}
+
+// JSON that maps each word of the machine code back to the source, e.g.
+// {
+//   "files": [
+//     "examples/hello_world.asm",
+//     ...
+//   ],
+//   "words": [
+//     {
+//       "address": 0,
+//       "file": 0,
+//       "start": 122,
+//       "end": 137,
+//       "line": 9,
+//       "column": 2
+//     },
+//     ...
+//   ],
+//   "scopes": [...],
+//   "symbols": [...]
+// }
+pub fn to_debug_info(assembly: &mut Assembly) -> String {
+    let mut files = Files::default();
+
+    let words = words(assembly, &mut files);
+    let scopes = scopes(assembly, &mut files);
+    let symbols = symbols(assembly, &mut files);
+
+    let debug_info = DebugInfo {
+        files: files.0.iter().map(|src| src.to_string()).collect(),
+        words,
+        scopes,
+        symbols,
+        sources: Vec::new(),
+    };
+
+    // the debug information only has strings, numbers, and lists, so it can always be serialized
+    let mut json =
+        serde_json::to_string_pretty(&debug_info).expect("The debug information can be serialized");
+    json.push('\n');
+    json
+}
+
+fn words(assembly: &mut Assembly, files: &mut Files) -> Vec<WordInfo> {
+    let mut words = Vec::new();
+
+    for statement in &assembly.statements {
+        // blocks are mapped by the statements in them
+        if statement.kind == StatementKind::Block {
+            continue;
+        }
+
+        // reserved bss words aren't in the machine code
+        let num_words = assembly.words(statement).len() as u32;
+
+        let location = location(&mut assembly.cache, files, &statement.span);
+        for address in statement.addresses.start..statement.addresses.start + num_words {
+            words.push(WordInfo {
+                address,
+                location: location.clone(),
+            });
+        }
+    }
+
+    words
+}
+
+fn scopes(assembly: &mut Assembly, files: &mut Files) -> Vec<ScopeInfo> {
+    let mut scopes = Vec::new();
+
+    // whole files; each file is generated all at once, so its statements are next to each other
+    // a file with statements in both ROM and RAM has a scope for each
+    let mut i = 0;
+    while i < assembly.statements.len() {
+        let src = assembly.statements[i].span.src;
+        let file_statements: Vec<_> = assembly.statements[i..]
+            .iter()
+            .take_while(|statement| statement.span.src == src)
+            .collect();
+        i += file_statements.len();
+
+        let end = assembly
+            .cache
+            .fetch(&src)
+            .map_or(0, |source| source.text().len());
+        let span = Span::new(src, 0..end);
+
+        for in_ram in [false, true] {
+            let addresses = file_statements
+                .iter()
+                .filter(|statement| statement.section.in_ram() == in_ram)
+                .map(|statement| statement.addresses.clone());
+
+            let Some(start_address) = addresses.clone().map(|addresses| addresses.start).min()
+            else {
+                continue;
+            };
+            let end_address = addresses
+                .map(|addresses| addresses.end)
+                .max()
+                .unwrap_or(start_address);
+
+            scopes.push(ScopeInfo {
+                name: None,
+                start_address,
+                end_address,
+                location: location(&mut assembly.cache, files, &span),
+                depth: 0,
+            });
+        }
+    }
+
+    let block_names = assembly.block_names();
+
+    for (statement, name) in assembly.statements.iter().zip(block_names) {
+        let Some(name) = name else {
+            continue;
+        };
+
+        scopes.push(ScopeInfo {
+            name: Some(name),
+            start_address: statement.addresses.start,
+            end_address: statement.addresses.end,
+            location: location(&mut assembly.cache, files, &statement.span),
+            depth: statement.depth + 1,
+        });
+    }
+
+    scopes
+}
+
+fn symbols(assembly: &mut Assembly, files: &mut Files) -> Vec<SymbolInfo> {
+    let mut symbols = Vec::new();
+
+    for symbol in &assembly.symbols {
+        symbols.push(SymbolInfo {
+            name: symbol.name.clone(),
+            kind: symbol.kind.to_string(),
+            value: symbol.value.clone(),
+            number: symbol.number,
+            exported: symbol.exported,
+            scope: symbol.scope.clone(),
+            location: location(&mut assembly.cache, files, &symbol.span),
+        });
+    }
+
+    symbols
+}
+
+// the files are referred to by their index so that their paths aren't repeated for every word
+#[derive(Default)]
+struct Files(Vec<Intern<Src>>);
+
+impl Files {
+    fn index(&mut self, src: Intern<Src>) -> usize {
+        match self.0.iter().position(|file| *file == src) {
+            Some(index) => index,
+            None => {
+                self.0.push(src);
+                self.0.len() - 1
+            }
+        }
+    }
+}
+
+fn location(cache: &mut SrcCache, files: &mut Files, span: &Span) -> Location {
+    let (line, column) = line_column(cache, span).unwrap_or_default();
+
+    Location {
+        file: files.index(span.src),
+        start: span.start(),
+        end: span.end(),
+        line,
+        column,
+    }
+}
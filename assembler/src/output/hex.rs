@@ -0,0 +1,9 @@
+// one word per line as 8 hex digits, which is the format $readmemh reads
+pub fn to_hex(machine_code: &[u32]) -> String {
+    let mut machine_code_string = String::with_capacity(machine_code.len() * 9);
+    for opcode in machine_code {
+        machine_code_string.push_str(&format!("{:0>8x}\n", opcode));
+    }
+
+    machine_code_string
+}
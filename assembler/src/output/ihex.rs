@@ -0,0 +1,58 @@
+use crate::output::{Endianness, bin};
+
+const BYTES_PER_RECORD: usize = 16;
+
+const DATA_RECORD: u8 = 0x00;
+const END_OF_FILE_RECORD: u8 = 0x01;
+const EXTENDED_LINEAR_ADDRESS_RECORD: u8 = 0x04;
+
+// Intel HEX, using byte addresses (i.e. word address * 4) with each word in the given byte order;
+// the machine code starts at the given word address (i.e. the start of the ROM)
+pub fn to_ihex(machine_code: &[u32], start: u32, endianness: Endianness) -> String {
+    let bytes = bin::to_bin(machine_code, endianness);
+    let mut ihex = String::new();
+
+    // the upper 16 bits of the address are given by extended linear address records, and are 0 until
+    // the first one
+    let mut upper_address = 0;
+
+    for (i, chunk) in bytes.chunks(BYTES_PER_RECORD).enumerate() {
+        let address = start * 4 + (i * BYTES_PER_RECORD) as u32;
+
+        if address >> 16 != upper_address {
+            upper_address = address >> 16;
+            write_record(
+                &mut ihex,
+                EXTENDED_LINEAR_ADDRESS_RECORD,
+                0,
+                &(upper_address as u16).to_be_bytes(),
+            );
+        }
+
+        write_record(&mut ihex, DATA_RECORD, address as u16, chunk);
+    }
+
+    write_record(&mut ihex, END_OF_FILE_RECORD, 0, &[]);
+
+    ihex
+}
+
+fn write_record(ihex: &mut String, record_type: u8, address: u16, data: &[u8]) {
+    let mut record = vec![data.len() as u8];
+    record.extend_from_slice(&address.to_be_bytes());
+    record.push(record_type);
+    record.extend_from_slice(data);
+
+    // two's complement of the sum of all the bytes in the record
+    let checksum = record
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+    record.push(checksum);
+
+    ihex.push(':');
+    for byte in record {
+        ihex.push_str(&format!("{:02X}", byte));
+    }
+    ihex.push('\n');
+}
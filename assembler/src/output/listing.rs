@@ -0,0 +1,108 @@
+use ariadne::Cache;
+
+use crate::SrcCache;
+use crate::assembly::{AssembledStatement, Assembly, StatementKind, line_column};
+use crate::disassembler::instruction::Fields;
+
+const FIELDS_WIDTH: usize = 56;
+
+// the machine code alongside the statements that generated it, grouped by file; files with no words
+// (e.g. the target module, which only has assignments) are not listed
+// e.g. `0012  0030000a  cond=none op=LDI a=r3 immediate=0x000a  14:5    ld r3, 10;`
+pub fn to_listing(assembly: &mut Assembly) -> String {
+    let mut listing = String::new();
+
+    let mut start = 0;
+    while start < assembly.statements.len() {
+        let src = assembly.statements[start].span.src;
+        let end = start
+            + assembly.statements[start..]
+                .iter()
+                .take_while(|statement| statement.span.src == src)
+                .count();
+        let file_statements = start..end;
+        start = end;
+
+        if assembly.statements[file_statements.clone()]
+            .iter()
+            .all(|statement| assembly.words(statement).is_empty())
+        {
+            continue;
+        }
+
+        if !listing.is_empty() {
+            listing.push('\n');
+        }
+        listing.push_str(&format!("{}:\n", src));
+        listing.push_str(&format!(
+            "{:<4}  {:<8}  {:<FIELDS_WIDTH$}  SOURCE\n",
+            "ADDR", "WORD", "FIELDS"
+        ));
+
+        for i in file_statements {
+            let statement = assembly.statements[i].clone();
+            let words = assembly.words(&statement).to_vec();
+            listing.push_str(&list_statement(&mut assembly.cache, &statement, &words));
+        }
+    }
+
+    listing
+}
+
+// a line for each word of the statement, or a single line if it has no words
+fn list_statement(cache: &mut SrcCache, statement: &AssembledStatement, words: &[u32]) -> String {
+    let (location, text) = source(cache, statement);
+    let indent = "    ".repeat(statement.depth);
+    let source = format!("{:<8}{}{}", location, indent, text);
+
+    // blocks are listed by the statements in them
+    if words.is_empty() || statement.kind == StatementKind::Block {
+        return format!(
+            "{:04x}  {:8}  {:FIELDS_WIDTH$}  {}\n",
+            statement.addresses.start, "", "", source
+        );
+    }
+
+    let mut lines = String::new();
+    for (i, word) in words.iter().enumerate() {
+        let fields = match statement.kind {
+            StatementKind::Operation | StatementKind::Startup => Fields(*word).to_string(),
+            StatementKind::Org | StatementKind::Align => "fill".to_string(),
+            _ => "data".to_string(),
+        };
+
+        // the source is only written next to the first word of the statement
+        let line = format!(
+            "{:04x}  {:08x}  {:<FIELDS_WIDTH$}  {}",
+            statement.addresses.start + i as u32,
+            word,
+            fields,
+            if i == 0 { source.as_str() } else { "" }
+        );
+        lines.push_str(&format!("{}\n", line.trim_end()));
+    }
+
+    lines
+}
+
+// the line:column of the statement and the first line of its text
+fn source(cache: &mut SrcCache, statement: &AssembledStatement) -> (String, String) {
+    let location = match line_column(cache, &statement.span) {
+        Some((line, column)) => format!("{}:{}", line, column),
+        None => String::new(),
+    };
+
+    let Ok(source) = cache.fetch(&statement.span.src) else {
+        return (location, String::new());
+    };
+
+    let text = source
+        .text()
+        .get(statement.span.range())
+        .and_then(|text| text.lines().next())
+        .unwrap_or_default()
+        .trim_end()
+        .to_string();
+
+    (location, text)
+}
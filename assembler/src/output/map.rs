@@ -0,0 +1,50 @@
+use crate::assembly::{Assembly, line_column};
+
+// the first line of every map file, which the disassembler uses to tell a map file apart from a
+// symbol file
+pub(crate) const MAP_HEADER: &str = "// number name kind export scope location value";
+
+// every label and assignment, one per line, e.g.
+// `0x0014  print  address  export  -  examples/lib/print.asm:4:8  0x0014`
+// the columns are separated by whitespace (at least one space, even if a column is wider than its
+// padding, e.g. a long name or a 32-bit number), and the first two are the number (or "-") and the
+// name so that the map can be given to the disassembler as a symbol file
+// the value as it would be written in assembly is last since strings can contain whitespace
+pub fn to_map(assembly: &mut Assembly) -> String {
+    let mut map = String::new();
+    map.push_str(MAP_HEADER);
+    map.push('\n');
+
+    for symbol in &assembly.symbols {
+        let number = match symbol.number {
+            Some(number) => format!("0x{:04x}", number),
+            None => "-".to_string(),
+        };
+
+        let export = if symbol.exported { "export" } else { "local" };
+
+        let scope = if symbol.scope.is_empty() {
+            "-".to_string()
+        } else {
+            symbol.scope.join("/")
+        };
+
+        let location = match line_column(&mut assembly.cache, &symbol.span) {
+            Some((line, column)) => format!("{}:{}:{}", symbol.span.src, line, column),
+            None => symbol.span.src.to_string(),
+        };
+
+        map.push_str(&format!(
+            "{:<7} {:<23} {:<15} {:<7} {:<15} {:<39} {}\n",
+            number,
+            symbol.name,
+            symbol.kind.to_string(),
+            export,
+            scope,
+            location,
+            symbol.value
+        ));
+    }
+
+    map
+}
@@ -0,0 +1,39 @@
+use crate::error::Error;
+use crate::output::check_depth;
+
+// Altera/Intel memory initialization file, with the words after the machine code set to 0 so that
+// the whole memory is initialized
+pub fn to_mif(machine_code: &[u32], depth: usize) -> Result<String, Error> {
+    check_depth(machine_code, depth)?;
+
+    let mut mif = String::new();
+    mif.push_str(&format!("DEPTH = {};\n", depth));
+    mif.push_str("WIDTH = 32;\n");
+    mif.push_str("ADDRESS_RADIX = HEX;\n");
+    mif.push_str("DATA_RADIX = HEX;\n");
+    mif.push_str("CONTENT\n");
+    mif.push_str("BEGIN\n");
+
+    for (address, opcode) in machine_code.iter().enumerate() {
+        mif.push_str(&format!("{:04X} : {:08X};\n", address, opcode));
+    }
+
+    match depth - machine_code.len() {
+        0 => (),
+        1 => {
+            mif.push_str(&format!("{:04X} : {:08X};\n", machine_code.len(), 0));
+        }
+        _ => {
+            mif.push_str(&format!(
+                "[{:04X}..{:04X}] : {:08X};\n",
+                machine_code.len(),
+                depth - 1,
+                0
+            ));
+        }
+    }
+
+    mif.push_str("END;\n");
+
+    Ok(mif)
+}
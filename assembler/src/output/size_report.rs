@@ -0,0 +1,157 @@
+use crate::assembly::{Assembly, StatementKind};
+
+// the number of ROM words generated by each file, top-level block, and exported label, along with
+// how much of the ROM and RAM is used
+// each breakdown is sorted by the number of words, largest first
+pub fn to_size_report(assembly: &Assembly) -> String {
+    let mut report = String::new();
+
+    let (rom, ram) = (assembly.target.rom, assembly.target.ram);
+
+    let used = assembly.machine_code.len() as u32;
+    report.push_str(&format!(
+        "ROM usage: {} of {} words ({:.2}%), {} words free\n",
+        used,
+        rom.depth,
+        used as f64 / rom.depth as f64 * 100.0,
+        rom.depth.saturating_sub(used)
+    ));
+
+    // the RAM is used up to the last data or bss word, including any gaps
+    let ram_used = assembly
+        .statements
+        .iter()
+        .filter(|statement| statement.section.in_ram())
+        .map(|statement| statement.addresses.end - ram.start)
+        .max()
+        .unwrap_or(0);
+    report.push_str(&format!(
+        "RAM usage: {} of {} words ({:.2}%), {} words free\n",
+        ram_used,
+        ram.depth,
+        ram_used as f64 / ram.depth as f64 * 100.0,
+        ram.depth.saturating_sub(ram_used)
+    ));
+
+    if let Some(load_image) = &assembly.load_image {
+        report.push_str(&format!(
+            "Load image: {} words at 0x{:04x}, copied to 0x{:04x} at startup\n",
+            load_image.data.len(),
+            load_image.address,
+            load_image.data.start
+        ));
+    }
+
+    write_section(&mut report, "file", used, files(assembly));
+    write_section(
+        &mut report,
+        "top-level block",
+        used,
+        top_level_blocks(assembly),
+    );
+    write_section(
+        &mut report,
+        "exported label",
+        used,
+        exported_labels(assembly),
+    );
+
+    report
+}
+
+// (name, address, number of words)
+type Row = (String, u32, u32);
+
+// the percent is of all the words in the machine code
+fn write_section(report: &mut String, title: &str, total: u32, mut rows: Vec<Row>) {
+    rows.sort_by(|(_, _, words1), (_, _, words2)| words2.cmp(words1));
+
+    report.push('\n');
+    report.push_str(&format!("By {}:\n", title));
+    report.push_str("   WORDS  PERCENT  ADDRESS   NAME\n");
+
+    for (name, address, words) in rows {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            words as f64 / total as f64 * 100.0
+        };
+
+        report.push_str(&format!(
+            "  {:>6}  {:>6.2}%  0x{:04x}    {}\n",
+            words, percent, address, name
+        ));
+    }
+}
+
+fn files(assembly: &Assembly) -> Vec<Row> {
+    let mut rows: Vec<Row> = Vec::new();
+
+    // each file is generated all at once, so its statements are next to each other
+    for statement in assembly
+        .statements
+        .iter()
+        .filter(|statement| statement.depth == 0 && !statement.section.in_ram())
+    {
+        let name = statement.span.src.to_string();
+        let words = statement.addresses.len() as u32;
+
+        match rows.last_mut() {
+            Some((file, _, file_words)) if *file == name => *file_words += words,
+            _ => rows.push((name, statement.addresses.start, words)),
+        }
+    }
+
+    // files with no words (e.g. the target module, which only has assignments) are left out
+    rows.retain(|(_, _, words)| *words > 0);
+    rows
+}
+
+fn top_level_blocks(assembly: &Assembly) -> Vec<Row> {
+    assembly
+        .statements
+        .iter()
+        .zip(assembly.block_names())
+        .filter(|(statement, _)| statement.depth == 0 && !statement.section.in_ram())
+        .filter_map(|(statement, name)| {
+            Some((
+                format!("{} ({})", name?, statement.span.src),
+                statement.addresses.start,
+                statement.addresses.len() as u32,
+            ))
+        })
+        .collect()
+}
+
+// an exported label is counted up to the next label in the same block
+fn exported_labels(assembly: &Assembly) -> Vec<Row> {
+    let mut rows = Vec::new();
+
+    for (i, label) in assembly.statements.iter().enumerate() {
+        let Some(symbol) = assembly
+            .label_symbol(label)
+            .filter(|symbol| symbol.exported && !label.section.in_ram())
+        else {
+            continue;
+        };
+
+        let words = assembly.statements[i + 1..]
+            .iter()
+            .take_while(|statement| {
+                statement.span.src == label.span.src && statement.depth >= label.depth
+            })
+            .filter(|statement| statement.depth == label.depth)
+            .take_while(|statement| statement.kind != StatementKind::Label)
+            .filter(|statement| !statement.section.in_ram())
+            .map(|statement| statement.addresses.len() as u32)
+            .sum();
+
+        rows.push((
+            format!("{} ({})", symbol.name, label.span.src),
+            label.addresses.start,
+            words,
+        ));
+    }
+
+    rows
+}
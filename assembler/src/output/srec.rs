@@ -0,0 +1,57 @@
+use crate::output::{Endianness, bin};
+
+const BYTES_PER_RECORD: usize = 16;
+
+// Motorola S-record, using byte addresses (i.e. word address * 4) with each word in the given byte
+// order; the machine code starts at the given word address (i.e. the start of the ROM). The smallest
+// address size that fits the whole image is used
+pub fn to_srec(machine_code: &[u32], start: u32, endianness: Endianness) -> String {
+    let bytes = bin::to_bin(machine_code, endianness);
+    let mut srec = String::new();
+
+    let address_size = match start as usize * 4 + bytes.len() {
+        len if len <= 1 << 16 => 2,
+        len if len <= 1 << 24 => 3,
+        _ => 4,
+    };
+
+    // header with no data
+    write_record(&mut srec, 0, 0, 2, &[]);
+
+    let mut num_records = 0;
+    for (i, chunk) in bytes.chunks(BYTES_PER_RECORD).enumerate() {
+        let address = start * 4 + (i * BYTES_PER_RECORD) as u32;
+
+        // S1, S2, and S3 are data records with a 2, 3, and 4 byte address respectively
+        write_record(&mut srec, address_size - 1, address, address_size, chunk);
+        num_records += 1;
+    }
+
+    // S5 and S6 are record counts with a 2 and 3 byte count respectively
+    if num_records < 1 << 16 {
+        write_record(&mut srec, 5, num_records, 2, &[]);
+    } else {
+        write_record(&mut srec, 6, num_records, 3, &[]);
+    }
+
+    // S9, S8, and S7 terminate the data records of S1, S2, and S3 respectively
+    write_record(&mut srec, 11 - address_size, 0, address_size, &[]);
+
+    srec
+}
+
+fn write_record(srec: &mut String, record_type: u8, address: u32, address_size: u8, data: &[u8]) {
+    let mut record = vec![address_size + data.len() as u8 + 1]; // + 1 for the checksum
+    record.extend_from_slice(&address.to_be_bytes()[4 - address_size as usize..]);
+    record.extend_from_slice(data);
+
+    // one's complement of the least significant byte of the sum of all the bytes in the record
+    let checksum = !record.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    record.push(checksum);
+
+    srec.push_str(&format!("S{}", record_type));
+    for byte in record {
+        srec.push_str(&format!("{:02X}", byte));
+    }
+    srec.push('\n');
+}
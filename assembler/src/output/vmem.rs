@@ -0,0 +1,42 @@
+use crate::error::Error;
+use crate::output::{Region, Segment};
+use crate::target::Target;
+
+// the contents of the ROM and RAM as files for $readmemh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vmem {
+    pub rom: String,
+    pub ram: Option<String>, // none if nothing is placed in RAM
+}
+
+// verilog hex with an @address record before each segment
+// the addresses are indexes into the memory of the region (i.e. relative to the start of the
+// region), which is what rom.sv and mem.sv expect
+pub fn to_vmem(segments: &[Segment], target: &Target) -> Result<Vmem, Error> {
+    let mut rom = String::new();
+    let mut ram = None;
+
+    for segment in segments.iter().filter(|segment| !segment.words.is_empty()) {
+        let (region, vmem) = if target.rom.contains(segment) {
+            (target.rom, &mut rom)
+        } else if target.ram.contains(segment) {
+            (target.ram, ram.get_or_insert_with(String::new))
+        } else {
+            return Err(Error::Bare(format!(
+                "Words at {} are not entirely in ROM ({}) or RAM ({}) of the target \"{}\"",
+                segment, target.rom, target.ram, target.name
+            )));
+        };
+
+        write_segment(vmem, region, segment);
+    }
+
+    Ok(Vmem { rom, ram })
+}
+
+fn write_segment(vmem: &mut String, region: Region, segment: &Segment) {
+    vmem.push_str(&format!("@{:04x}\n", segment.address - region.start));
+    for opcode in &segment.words {
+        vmem.push_str(&format!("{:0>8x}\n", opcode));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::Span;
 use crate::ast::{
     Assignment, Block, File, FullMnemonic, Import, ImportSpecifier, Label, Modifier, NamedImport,
-    Operation, Statement,
+    Operation, Placement, Statement,
 };
 use crate::span::Spanned;
+use crate::src::{Src, TARGET_MODULE};
 use crate::symbol_table::SymbolTable;
 use chumsky::input::StrInput;
 use chumsky::prelude::*;
@@ -113,6 +114,37 @@ fn statement_parser<'src, I: Input<'src>>() -> impl Parser<'src, I, Statement, E
                 .padded_by(comment_pad())
                 .then_ignore(just(';'))
                 .map(Statement::Operation),
+            placement_parser("org")
+                .padded_by(comment_pad())
+                .then_ignore(just(';'))
+                .map(Statement::Org),
+            placement_parser("align")
+                .padded_by(comment_pad())
+                .then_ignore(just(';'))
+                .map(Statement::Align),
+            text::keyword("section")
+                .ignore_then(keywords::section_parser().padded_by(comment_pad()))
+                .padded_by(comment_pad())
+                .then_ignore(just(';'))
+                .map(Statement::Section),
+            text::keyword("startup")
+                .ignore_then(
+                    expression::expression_parser()
+                        .spanned()
+                        .padded_by(comment_pad()),
+                )
+                .padded_by(comment_pad())
+                .then_ignore(just(';'))
+                .map(Statement::Startup),
+            text::keyword("reserve")
+                .ignore_then(
+                    expression::expression_parser()
+                        .spanned()
+                        .padded_by(comment_pad()),
+                )
+                .padded_by(comment_pad())
+                .then_ignore(just(';'))
+                .map(Statement::Reserve),
             label
                 .padded_by(comment_pad())
                 .then_ignore(just(':'))
@@ -181,6 +213,29 @@ fn operation_parser<'src, I: Input<'src>>() -> impl Parser<'src, I, Operation, E
         .labelled("operation")
 }
 
+// e.g. `org 0x0100;` or `align 4, 0xffffffff;`
+fn placement_parser<'src, I: Input<'src>>(
+    directive: &'static str,
+) -> impl Parser<'src, I, Placement, Extra<'src>> {
+    text::keyword(directive)
+        .ignore_then(
+            expression::expression_parser()
+                .spanned()
+                .padded_by(comment_pad()),
+        )
+        .then(
+            just(',')
+                .ignore_then(
+                    expression::expression_parser()
+                        .spanned()
+                        .padded_by(comment_pad()),
+                )
+                .or_not(),
+        )
+        .map(|(operand, fill)| Placement { operand, fill })
+        .labelled(directive)
+}
+
 fn import_parser<'src, I: Input<'src>>() -> impl Parser<'src, I, Import, Extra<'src>> {
     let named_import = text::ident()
         .map(|s: &str| Intern::new(s.to_string()))
@@ -213,6 +268,11 @@ fn import_parser<'src, I: Input<'src>>() -> impl Parser<'src, I, Import, Extra<'
         )
         .then(text::keyword("from").padded_by(comment_pad()).ignore_then(
             expression::string_parser().try_map(|import_string, span: Span| {
+                // the target module is built in, so it isn't relative to the file
+                if import_string == TARGET_MODULE {
+                    return Ok(Spanned::new(Intern::new(Src::target_module()), span));
+                }
+
                 // there seems to be a bug in chumsky where this span is being changed to a single character
                 // instead of using the full string span
                 let import_src = span
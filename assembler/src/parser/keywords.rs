@@ -1,3 +1,4 @@
+use crate::assembly::Section;
 use crate::ast::{
     AluModifier, AsmMnemonic, BinaryAluCpuMnemonic, Condition, Register, UnaryAluCpuMnemonic,
 };
@@ -89,3 +90,13 @@ pub(super) fn condition_parser<'src, I: Input<'src>>()
     ))
     .labelled("condition")
 }
+
+pub(super) fn section_parser<'src, I: Input<'src>>() -> impl Parser<'src, I, Section, Extra<'src>> {
+    choice((
+        text::keyword("text").to(Section::Text),
+        text::keyword("rodata").to(Section::Rodata),
+        text::keyword("data").to(Section::Data),
+        text::keyword("bss").to(Section::Bss),
+    ))
+    .labelled("section")
+}
@@ -11,6 +11,9 @@ use std::{
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub struct Src(PathBuf);
 
+// the name of the built-in module of symbols generated from the target, i.e. `import * from "target";`
+pub(crate) const TARGET_MODULE: &str = "target";
+
 impl Src {
     pub fn new(pathbuf: PathBuf) -> io::Result<Self> {
         let pathbuf = pathbuf.canonicalize()?;
@@ -18,13 +21,25 @@ impl Src {
         Ok(Src(pathbuf))
     }
 
+    // the target module isn't a file, so its path is only used as a name; its source is put in the
+    // cache before any files are read
+    pub(crate) fn target_module() -> Self {
+        Src(PathBuf::from(format!("<{}>", TARGET_MODULE)))
+    }
+
     pub(crate) fn get_relative(&self, relative_path: &Path) -> io::Result<Src> {
         // parent() should* always return something because src is a file (we read it as a file)
-        Src::new(
-            self.parent()
-                .unwrap_or_else(|| panic!("Could not get parent directory of '{}'", self))
-                .join(relative_path),
-        )
+        let pathbuf = self
+            .parent()
+            .unwrap_or_else(|| panic!("Could not get parent directory of '{}'", self))
+            .join(relative_path);
+
+        match Src::new(pathbuf.clone()) {
+            // a file that doesn't exist is only an error if it is read, as the imports of an object
+            // are resolved by the linker (e.g. from a prebuilt library object without its source)
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Src(pathbuf)),
+            src => src,
+        }
     }
 }
 
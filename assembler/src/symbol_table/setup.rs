@@ -1,6 +1,7 @@
 use crate::{
     ast::Ast,
     error::{Error, RecoveredError},
+    target::Target,
 };
 
 mod imports;
@@ -8,7 +9,7 @@ mod labels;
 mod symbols;
 
 impl Ast {
-    pub(crate) fn init_symbol_tables(&mut self) -> Result<(), Vec<Error>> {
+    pub(crate) fn init_symbol_tables(&mut self, target: &Target) -> Result<(), Vec<Error>> {
         let mut errors = Vec::new();
 
         let file_exports_map = match self.add_symbols() {
@@ -24,7 +25,7 @@ impl Ast {
             Err(mut export_errors) => errors.append(&mut export_errors),
         }
 
-        match self.set_labels() {
+        match self.set_labels(target) {
             Ok(_) => (),
             Err(mut import_errors) => errors.append(&mut import_errors),
         }
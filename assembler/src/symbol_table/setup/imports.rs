@@ -1,13 +1,17 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
 
 use ariadne::Fmt;
 
 use crate::{
     ast::{Ast, Block, File, Import, ImportSpecifier, Statement},
     error::{ATTENTION_COLOR, Error, SpannedError},
+    expression::expression_result::{ExpressionResult, Number, RelativeTo},
     span::Spanned,
     symbol_table::{
-        STEntry, SymbolTable,
+        STEntry, Symbol, SymbolTable, SymbolValue,
         setup::symbols::{ExportMap, FileExportMap},
     },
 };
@@ -21,7 +25,7 @@ impl Ast {
         let mut errors = Vec::new();
 
         for file in self.files.iter_mut() {
-            match file.as_mut_ref().add_imports(file_exports_map) {
+            match file.as_mut_ref().add_imports(file_exports_map, self.object) {
                 Ok(_) => (),
                 Err(mut import_errors) => errors.append(&mut import_errors),
             }
@@ -36,15 +40,23 @@ impl Ast {
 }
 
 impl Spanned<&mut File> {
-    fn add_imports(&mut self, file_exports_map: &FileExportMap) -> Result<(), Vec<Error>> {
+    fn add_imports(
+        &mut self,
+        file_exports_map: &FileExportMap,
+        object: bool,
+    ) -> Result<(), Vec<Error>> {
         self.span
             .spanned(&mut self.block)
-            .add_imports(file_exports_map)
+            .add_imports(file_exports_map, object)
     }
 }
 
 impl Spanned<&mut Block> {
-    fn add_imports(&mut self, file_exports_map: &FileExportMap) -> Result<(), Vec<Error>> {
+    fn add_imports(
+        &mut self,
+        file_exports_map: &FileExportMap,
+        object: bool,
+    ) -> Result<(), Vec<Error>> {
         let mut errors = Vec::new();
 
         // to satisfy borrow checker
@@ -54,7 +66,7 @@ impl Spanned<&mut Block> {
         self.statements.retain_mut(|statement| {
             match statement
                 .as_mut_ref()
-                .add_imports(&symbol_table, file_exports_map)
+                .add_imports(&symbol_table, file_exports_map, object)
             {
                 Ok(_) => true,
                 Err(mut statement_errors) => {
@@ -77,27 +89,38 @@ impl Spanned<&mut Statement> {
         &mut self,
         symbol_table: &Rc<RefCell<SymbolTable>>,
         file_exports_map: &FileExportMap,
+        object: bool,
     ) -> Result<(), Vec<Error>> {
         let mut errors = Vec::new();
 
         match self.val {
             Statement::Import(import) => {
                 // the import files' exports
-                let exports = file_exports_map.get(&import.src).unwrap_or_else(||
+                let result = match file_exports_map.get(&import.src) {
+                    Some(exports) => symbol_table.borrow_mut().import(import, exports),
+
+                    // other files aren't in the ast of an object, as they are linked separately
+                    None if object => symbol_table.borrow_mut().import_linked(import),
+
                     // add_symbols() should have already created it
-                    panic!(
+                    None => panic!(
                         "Attempted to import '{}' at {} when the exporter's symbol table has not been filled",
                         import.src.val, import.src.span
-                    ));
+                    ),
+                };
 
-                match symbol_table.borrow_mut().import(import, exports) {
+                match result {
                     Ok(_) => (),
                     Err(import_error) => errors.push(import_error),
                 }
             }
 
             Statement::Block(sub_block) => {
-                match self.span.spanned(sub_block).add_imports(file_exports_map) {
+                match self
+                    .span
+                    .spanned(sub_block)
+                    .add_imports(file_exports_map, object)
+                {
                     Ok(_) => (),
                     Err(mut sub_errors) => errors.append(&mut sub_errors),
                 }
@@ -190,3 +213,53 @@ impl SymbolTable {
         Ok(())
     }
 }
+
+impl SymbolTable {
+    // the symbols imported from a file that is linked separately are numbers relative to the
+    // address of the symbol, which the linker finds in the exports of the other objects
+    fn import_linked(&mut self, import: &Import) -> Result<(), Error> {
+        let ImportSpecifier::Named(named_imports) = &import.specifier.val else {
+            return Err(Error::Spanned(Box::new(
+                SpannedError::new(import.specifier.span, "Glob import in object")
+                    .with_label(format!(
+                        "The symbols exported by '{}' are not known until the object is linked",
+                        import.src.val.fg(ATTENTION_COLOR)
+                    ))
+                    .with_help(format!(
+                        "Try importing each symbol by name: {}",
+                        "import <name>, <other name> from ...".fg(ATTENTION_COLOR)
+                    )),
+            )));
+        };
+
+        for named_import in named_imports {
+            let import_key = named_import
+                .alias
+                .as_ref()
+                .unwrap_or(&named_import.identifier);
+            let value = ExpressionResult::Number(Some(Number(
+                0,
+                Some(RelativeTo::Symbol(named_import.identifier.val)),
+            )));
+
+            self.try_insert(
+                import_key.val,
+                STEntry {
+                    symbol: Rc::new(RefCell::new(Symbol {
+                        value: named_import.identifier.span_to(SymbolValue::Result(value)),
+                        // the value is already known, so it is never evaluated in a symbol table
+                        symbol_table: Weak::new(),
+                    })),
+                    key_span: import_key.span,
+                    import_span: named_import
+                        .alias
+                        .is_none()
+                        .then_some(import.specifier.span),
+                    export_span: None,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,32 +1,41 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
+    assembly::Section,
     ast::{Ast, Block, Expression, File, Operation, Statement},
     error::{Error, RecoveredError, RecoveredResult, SpannedError},
     expression::{
         EvalReturn,
         expression_result::{ExpressionResult, Number},
     },
+    generator::{Cursor, STARTUP_WORDS},
     span::Spanned,
     symbol_table::{SymbolTable, SymbolValue},
+    target::Target,
 };
 
 impl Ast {
-    // calculates label addresses
-    pub(super) fn set_labels(&mut self) -> Result<(), Vec<Error>> {
+    // calculates label addresses; the labels of an object are relative to where it is placed
+    pub(super) fn set_labels(&mut self, target: &Target) -> Result<(), Vec<Error>> {
         let mut errors = Vec::new();
 
-        let mut address_accumulator = 0;
+        let mut cursor = if self.object {
+            Cursor::object()
+        } else {
+            Cursor::new(target)
+        };
         for file in self.files.iter_mut() {
-            address_accumulator = match file.as_mut_ref().set_labels(address_accumulator) {
-                Ok(address) => address,
-                Err(RecoveredError(address, mut label_errors)) => {
+            cursor = match file.as_mut_ref().set_labels(cursor) {
+                Ok(cursor) => cursor,
+                Err(RecoveredError(cursor, mut label_errors)) => {
                     errors.append(&mut label_errors);
-                    address
+                    cursor
                 }
             }
         }
 
+        self.load_image = cursor.load_image();
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -36,14 +45,16 @@ impl Ast {
 }
 
 impl Spanned<&mut File> {
-    fn set_labels(&mut self, start_address: u32) -> RecoveredResult<u32> {
-        self.span.spanned(&mut self.block).set_labels(start_address)
+    fn set_labels(&mut self, mut cursor: Cursor) -> RecoveredResult<Cursor> {
+        // each file starts in the text section
+        cursor.section = Section::default();
+        self.span.spanned(&mut self.block).set_labels(cursor)
     }
 }
 
 impl Spanned<&mut Block> {
-    fn set_labels(&mut self, start_address: u32) -> RecoveredResult<u32> {
-        let mut address = start_address;
+    fn set_labels(&mut self, start_cursor: Cursor) -> RecoveredResult<Cursor> {
+        let mut cursor = start_cursor;
         let mut errors = Vec::new();
 
         // to satisfy borrow checker
@@ -51,13 +62,13 @@ impl Spanned<&mut Block> {
 
         // filter out statements that cause errors so that an erroneous statement doesn't cascade errors
         self.statements.retain_mut(|statement| {
-            match statement.as_mut_ref().set_labels(address, &symbol_table) {
-                Ok(new_address) => {
-                    address = new_address;
+            match statement.as_mut_ref().set_labels(cursor, &symbol_table) {
+                Ok(new_cursor) => {
+                    cursor = new_cursor;
                     true
                 }
-                Err(RecoveredError(new_address, mut statement_errors)) => {
-                    address = new_address;
+                Err(RecoveredError(new_cursor, mut statement_errors)) => {
+                    cursor = new_cursor;
                     errors.append(&mut statement_errors);
                     false
                 }
@@ -65,9 +76,9 @@ impl Spanned<&mut Block> {
         });
 
         if errors.is_empty() {
-            Ok(address)
+            Ok(cursor)
         } else {
-            Err(RecoveredError(address, errors))
+            Err(RecoveredError(cursor, errors))
         }
     }
 }
@@ -75,17 +86,20 @@ impl Spanned<&mut Block> {
 impl Spanned<&mut Statement> {
     fn set_labels(
         &mut self,
-        start_address: u32,
+        start_cursor: Cursor,
         symbol_table: &Rc<RefCell<SymbolTable>>,
-    ) -> RecoveredResult<u32> {
-        let mut address = start_address;
+    ) -> RecoveredResult<Cursor> {
+        let mut cursor = start_cursor;
         let mut errors = Vec::new();
 
         match self.val {
             Statement::Label(label) => {
                 let result = label
                     .identifier
-                    .span_to(ExpressionResult::Number(Some(Number(address))));
+                    .span_to(ExpressionResult::Number(Some(Number(
+                        cursor.address(),
+                        cursor.relative_to(),
+                    ))));
 
                 let symbol_table = symbol_table.borrow_mut();
 
@@ -102,46 +116,66 @@ impl Spanned<&mut Statement> {
                     result.span.spanned(SymbolValue::Result(result.val));
             }
 
-            Statement::Block(sub_block) => match self.span.spanned(sub_block).set_labels(address) {
-                Ok(_) => (),
-                Err(RecoveredError(_, mut sub_errors)) => errors.append(&mut sub_errors),
-            },
+            // the sub block returns the addresses after it, which includes any gaps in it
+            // the section only lasts until the end of the block
+            Statement::Block(sub_block) => {
+                cursor = match self.span.spanned(sub_block).set_labels(cursor) {
+                    Ok(new_cursor) => new_cursor,
+                    Err(RecoveredError(new_cursor, mut sub_errors)) => {
+                        errors.append(&mut sub_errors);
+                        new_cursor
+                    }
+                };
+                cursor.section = start_cursor.section;
+            }
+
+            Statement::Section(section) => cursor.section = *section,
+
+            Statement::Org(org) if cursor.is_relocatable() => {
+                errors.push(Error::Spanned(Box::new(
+                    SpannedError::new(org.operand.span, "Origin in object")
+                        .with_label(
+                            "Objects are placed by the linker, so they cannot have an origin",
+                        )
+                        .with_help("Try using an align directive instead"),
+                )));
+                return Err(RecoveredError(cursor, errors));
+            }
             _ => (),
         }
 
-        address += match self.to_borrow().num_words(&symbol_table.borrow()) {
-            Ok(length) => length,
-            Err(error) => {
-                errors.push(error);
-                0
+        match self
+            .to_borrow()
+            .num_words(cursor.address(), &symbol_table.borrow())
+        {
+            Ok(length) => {
+                // reserved words are not initialized
+                if !matches!(self.val, Statement::Reserve(_)) {
+                    cursor.initialize(length);
+                }
+                cursor.advance(length);
             }
+            Err(error) => errors.push(error),
         };
 
         if errors.is_empty() {
-            Ok(address)
+            Ok(cursor)
         } else {
-            Err(RecoveredError(address, errors))
-        }
-    }
-}
-
-impl Block {
-    fn num_words(&self, symbol_table: &SymbolTable) -> Result<u32, Error> {
-        let mut num_words = 0;
-        for statement in &self.statements {
-            num_words += statement.as_ref().num_words(symbol_table)?;
+            Err(RecoveredError(cursor, errors))
         }
-
-        Ok(num_words)
     }
 }
 
 impl Spanned<&Statement> {
-    fn num_words(&self, symbol_table: &SymbolTable) -> Result<u32, Error> {
+    // blocks are not included, as their words are counted when setting their labels
+    fn num_words(&self, address: u32, symbol_table: &SymbolTable) -> Result<u32, Error> {
         match self.val {
             Statement::GenLiteral(literal) => self.span_to(literal).num_words(symbol_table),
-            Statement::Block(block) => block.num_words(&block.symbol_table.borrow()),
             Statement::Operation(operation) => operation.num_words(),
+            Statement::Org(org) => self.span_to(org).org_gap(address, symbol_table),
+            Statement::Align(align) => self.span_to(align).align_gap(address, symbol_table),
+            Statement::Reserve(size) => size.as_ref().reserve_size(symbol_table),
+            Statement::Startup(_) => Ok(STARTUP_WORDS),
             _ => Ok(0),
         }
     }
@@ -160,18 +194,7 @@ impl Spanned<&Expression> {
             ExpressionResult::Number(_number) => Ok(1),
             ExpressionResult::String(string) => {
                 let string = string.ok_or_else(|| {
-                    let mut error = SpannedError::new(self.span, "Unknown value of expression").with_label(
-                        "Expression needs to be determined, but is not",
-                    );
-                    for span in waiting_map.values() {
-                        error = error.with_label_span(*span, "This value is undetermined")
-                    }
-
-                    error.with_note(
-                        "This is ultimately caused because the expression is dependent on a future address (label), but the value of the expression would effect that address (label)",
-                    ).with_note(
-                        "For more info, see https://github.com/ablomm/ablomm-cpu/blob/main/docs/assembler/errors.md#unknown-value-of-expression",
-                    )
+                    Error::unknown_value(self.span, waiting_map.values().copied())
                 })?;
 
                 Ok(((string.len() as f32) / 4.0).ceil() as u32)
@@ -0,0 +1,189 @@
+// the memory map of the machine a program is assembled for, i.e. where its ROM, RAM, and memory
+// mapped devices are
+// targets are described by a TOML file (see cpu/simulation/simulator.toml), so that variants of the
+// simulator with different memories or devices don't need a different assembler
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::{error::Error, output::Region, utils::is_identifier};
+
+// the default target, which is the memory map of simulator.sv
+const SIMULATOR: &str = include_str!("../../cpu/simulation/simulator.toml");
+
+// the bits of the status register, which are the same for every target
+const STATUS_BITS: [(&str, u32); 6] = [
+    ("negative_bit", 5),
+    ("zero_bit", 4),
+    ("carry_bit", 3),
+    ("overflow_bit", 2),
+    ("interupt_enable_bit", 1), // the bit that controls if interrupts are enabled
+    ("supervisor_mode_bit", 0), // the bit that controls supervisor mode
+];
+
+// the CPU has 16-bit addresses
+pub(crate) const ADDRESS_SPACE: Region = Region {
+    start: 0x0000,
+    depth: 1 << 16,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub name: String,
+    pub rom: Region, // the text and rodata sections
+    pub ram: Region, // the data and bss sections
+
+    #[serde(default)]
+    pub devices: Vec<Device>,
+}
+
+// a memory mapped device, with a word for each register starting at its address
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Device {
+    pub name: String,
+    pub address: u32,
+
+    // the names of the registers in address order; a device with no registers is a single word
+    #[serde(default)]
+    pub registers: Vec<String>,
+
+    // numbers used with the device, e.g. the codes written to a register
+    #[serde(default)]
+    pub constants: IndexMap<String, u32>,
+}
+
+impl Device {
+    pub fn region(&self) -> Region {
+        Region {
+            start: self.address,
+            depth: self.registers.len().max(1) as u32,
+        }
+    }
+
+    // the name of each register and its address; a device with no registers is named by the device
+    pub fn register_symbols(&self) -> Vec<(String, u32)> {
+        if self.registers.is_empty() {
+            return vec![(self.name.clone(), self.address)];
+        }
+
+        self.registers
+            .iter()
+            .zip(self.address..)
+            .map(|(register, address)| (format!("{}_{}", self.name, register), address))
+            .collect()
+    }
+
+    // the constants are named after the device, e.g. `ctrl_start` of the timer is `timer_ctrl_start`
+    pub fn constant_symbols(&self) -> Vec<(String, u32)> {
+        self.constants
+            .iter()
+            .map(|(constant, value)| (format!("{}_{}", self.name, constant), *value))
+            .collect()
+    }
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        // should never panic because the simulator target is checked into the repo
+        let Ok(target) = Self::parse(SIMULATOR) else {
+            panic!("The simulator target is invalid");
+        };
+        target
+    }
+}
+
+impl Target {
+    pub fn parse(target: &str) -> Result<Self, Error> {
+        let target: Self = toml::from_str(target)
+            .map_err(|error| Error::Bare(format!("Invalid target description: {}", error)))?;
+
+        target.check()?;
+        Ok(target)
+    }
+
+    // the source of the built-in module imported with `import * from "target";`, which exports the
+    // status register bits, and each device register and constant, e.g.
+    //   export interupt_enable_bit = 1 << 1;
+    //   export tty = *0x4006;
+    //   export timer_ctrl = *0x4001;
+    //   export timer_ctrl_start = 1;
+    pub fn module(&self) -> String {
+        let mut module = String::new();
+        module.push_str(&format!("// generated from the target \"{}\"\n", self.name));
+
+        module.push_str("\n// status register bitmasks\n");
+        for (name, bit) in STATUS_BITS {
+            module.push_str(&format!("export {} = 1 << {};\n", name, bit));
+        }
+
+        for device in &self.devices {
+            module.push_str(&format!("\n// {}\n", device.name));
+            for (name, address) in device.register_symbols() {
+                module.push_str(&format!("export {} = *0x{:04x};\n", name, address));
+            }
+            for (name, value) in device.constant_symbols() {
+                module.push_str(&format!("export {} = {};\n", name, value));
+            }
+        }
+
+        module
+    }
+
+    // every region must fit in the address space without overlapping any other region
+    fn check(&self) -> Result<(), Error> {
+        let mut regions = vec![("ROM".to_string(), self.rom), ("RAM".to_string(), self.ram)];
+        for device in &self.devices {
+            if regions.iter().any(|(name, _)| *name == device.name) {
+                return Err(Error::Bare(format!(
+                    "The device \"{}\" in the target \"{}\" is defined more than once",
+                    device.name, self.name
+                )));
+            }
+
+            let symbols = device
+                .register_symbols()
+                .into_iter()
+                .chain(device.constant_symbols());
+            for (name, _) in symbols {
+                if !is_identifier(&name) {
+                    return Err(Error::Bare(format!(
+                        "The symbol \"{}\" of the device \"{}\" in the target \"{}\" is not a valid identifier",
+                        name, device.name, self.name
+                    )));
+                }
+            }
+
+            regions.push((device.name.clone(), device.region()));
+        }
+
+        for (i, (name, region)) in regions.iter().enumerate() {
+            if region.depth == 0 {
+                return Err(Error::Bare(format!(
+                    "{} in the target \"{}\" has a depth of 0",
+                    name, self.name
+                )));
+            }
+
+            if !ADDRESS_SPACE.contains_region(region) {
+                return Err(Error::Bare(format!(
+                    "{} in the target \"{}\" starts at 0x{:04x} with a depth of 0x{:x}, which is outside the address space ({})",
+                    name, self.name, region.start, region.depth, ADDRESS_SPACE
+                )));
+            }
+
+            if let Some((other_name, other)) = regions[..i]
+                .iter()
+                .find(|(_, other)| other.overlaps(region))
+            {
+                return Err(Error::Bare(format!(
+                    "{} ({}) overlaps {} ({}) in the target \"{}\"",
+                    name, region, other_name, other, self.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
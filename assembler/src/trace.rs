@@ -0,0 +1,245 @@
+// reads the instruction fetches from a value change dump of the simulator (see docs/cpu/simulator.md)
+// or the emulator, so they can be annotated with their disassembly and source
+// the signals are found by the end of their path, as Verilator puts the simulator in a TOP scope
+
+use std::collections::HashMap;
+
+use crate::{disassembler, error::Error, output::DebugInfo};
+
+// the value of the control unit state when an instruction is fetched (FETCH in cu.sv)
+const FETCH_STATE: u64 = 0;
+
+// an instruction read from memory into ir
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Fetch {
+    pub cycle: u64, // the number of rising clock edges before the fetch
+    pub time: u64,  // the time of the rising clock edge that loads ir
+    pub address: u32,
+    pub word: u32,
+}
+
+// the signals a fetch is read from; a fetch is a read in a cycle that loads ir, which is known from
+// ld_ir in the simulator, or the control unit state in the emulator (which has no ld_ir)
+struct Signals {
+    clk: String,
+    addr: String,
+    data: String,
+    rd: String,
+    fetch: FetchSignal,
+}
+
+enum FetchSignal {
+    LdIr(String),
+    State(String),
+}
+
+// the state of the dump as its value changes are read
+struct Dump<'a> {
+    signals: Signals,
+    values: HashMap<&'a str, u64>,
+    changes: Vec<(&'a str, u64)>, // the changes at the current time
+    time: u64,
+    cycles: u64,
+    fetches: Vec<Fetch>,
+}
+
+pub fn parse_fetches(vcd: &str) -> Result<Vec<Fetch>, Error> {
+    let mut tokens = vcd.split_whitespace();
+    let mut dump = Dump {
+        signals: parse_header(&mut tokens)?,
+        values: HashMap::new(),
+        changes: Vec::new(),
+        time: 0,
+        cycles: 0,
+        fetches: Vec::new(),
+    };
+
+    while let Some(token) = tokens.next() {
+        let invalid = || Error::Bare(format!("Invalid value change \"{}\"", token));
+
+        match token.as_bytes()[0] {
+            b'#' => {
+                dump.end_time();
+                dump.time = token[1..].parse().map_err(|_| invalid())?;
+            }
+            // $dumpvars, $dumpall, etc. contain value changes, but comments have to be skipped
+            b'$' if token == "$comment" => skip_section(&mut tokens),
+            b'$' => (),
+            b'b' | b'B' => dump.change(tokens.next().ok_or_else(invalid)?, &token[1..]),
+            // real numbers are never used by the signals
+            b'r' | b'R' => {
+                tokens.next();
+            }
+            b'0' | b'1' | b'x' | b'X' | b'z' | b'Z' => dump.change(&token[1..], &token[..1]),
+            _ => return Err(invalid()),
+        }
+    }
+    dump.end_time();
+
+    Ok(dump.fetches)
+}
+
+// a line for each fetch, with the cycle, address, word, disassembly, and the file, line, and text of
+// the statement that generated the word (if it is in the debug information), e.g.
+//       13  0x0001  00404006  ld *tty, r0                       examples/counter.asm:12  ld tty, num;
+pub fn annotate(fetches: &[Fetch], debug_info: &DebugInfo) -> String {
+    let symbols = debug_info.symbols();
+
+    let mut trace = String::new();
+    for fetch in fetches {
+        let statement = disassembler::disassemble_instruction(fetch.word, &symbols)
+            .unwrap_or_else(|| format!("0x{:08x}", fetch.word));
+
+        let mut line = format!(
+            "{:>8}  0x{:04x}  {:08x}  {:<32}",
+            fetch.cycle, fetch.address, fetch.word, statement
+        );
+        if let Some(word) = debug_info.word(fetch.address) {
+            line.push_str(&format!(
+                "  {}:{}  {}",
+                debug_info.file(word).unwrap_or_default(),
+                word.location.line,
+                debug_info.line(word).unwrap_or_default()
+            ));
+        }
+
+        trace.push_str(&format!("{}\n", line.trim_end()));
+    }
+
+    trace
+}
+
+impl<'a> Dump<'a> {
+    fn change(&mut self, id: &'a str, bits: &str) {
+        if self.signals.contains(id) {
+            self.changes.push((id, parse_bits(bits)));
+        }
+    }
+
+    // the values at a rising edge are the values before the changes at that time
+    fn end_time(&mut self) {
+        let clk = self.signals.clk.as_str();
+        let rising_edge = self.values.get(clk) == Some(&0)
+            && self
+                .changes
+                .iter()
+                .any(|(id, value)| *id == clk && *value == 1);
+
+        if rising_edge {
+            let loads_ir = match &self.signals.fetch {
+                FetchSignal::LdIr(ld_ir) => self.value(ld_ir) == 1,
+                FetchSignal::State(state) => self.value(state) == FETCH_STATE,
+            };
+
+            if loads_ir && self.value(&self.signals.rd) == 1 {
+                self.fetches.push(Fetch {
+                    cycle: self.cycles,
+                    time: self.time,
+                    address: self.value(&self.signals.addr) as u32 & 0xffff,
+                    word: self.value(&self.signals.data) as u32,
+                });
+            }
+            self.cycles += 1;
+        }
+
+        self.values.extend(self.changes.drain(..));
+    }
+
+    fn value(&self, id: &str) -> u64 {
+        self.values.get(id).copied().unwrap_or_default()
+    }
+}
+
+impl Signals {
+    // only the values of these signals are kept
+    fn contains(&self, id: &str) -> bool {
+        let fetch = match &self.fetch {
+            FetchSignal::LdIr(fetch) | FetchSignal::State(fetch) => fetch,
+        };
+        [&self.clk, &self.addr, &self.data, &self.rd, fetch]
+            .iter()
+            .any(|signal| signal.as_str() == id)
+    }
+}
+
+// finds the identifiers of the signals in the definitions
+fn parse_header<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Result<Signals, Error> {
+    let mut scope = Vec::new();
+    let mut vars = HashMap::new(); // the identifier of each signal, by its path
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "$scope" => {
+                let mut module = section(tokens);
+                scope.push(module.pop().unwrap_or_default());
+            }
+            "$upscope" => {
+                skip_section(tokens);
+                scope.pop();
+            }
+            // e.g. $var wire 32 # addr [31:0] $end
+            "$var" => {
+                if let [_, _, id, name, ..] = section(tokens)[..] {
+                    let path = [&scope[..], &[name]].concat().join(".");
+                    vars.insert(path, id.to_string());
+                }
+            }
+            "$enddefinitions" => {
+                skip_section(tokens);
+                break;
+            }
+            _ => skip_section(tokens),
+        }
+    }
+
+    let find = |path: &str| {
+        vars.iter()
+            .find(|(var, _)| *var == path || var.ends_with(&format!(".{}", path)))
+            .map(|(_, id)| id.clone())
+    };
+    let require = |path: &str| {
+        find(path)
+            .ok_or_else(|| Error::Bare(format!("The value change dump has no signal \"{}\"", path)))
+    };
+
+    let fetch = match (
+        find("simulator.cpu0.ld_ir"),
+        find("simulator.cpu0.cu0.state"),
+    ) {
+        (Some(ld_ir), _) => FetchSignal::LdIr(ld_ir),
+        (None, Some(state)) => FetchSignal::State(state),
+        (None, None) => {
+            return Err(Error::Bare(
+                "The value change dump has no signal \"simulator.cpu0.ld_ir\" or \"simulator.cpu0.cu0.state\""
+                    .to_string(),
+            ));
+        }
+    };
+
+    Ok(Signals {
+        clk: require("simulator.clk")?,
+        addr: require("simulator.addr")?,
+        data: require("simulator.data")?,
+        rd: require("simulator.rd")?,
+        fetch,
+    })
+}
+
+// the tokens of a section, up to its $end, e.g. "module simulator" of a scope
+fn section<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    tokens.take_while(|token| *token != "$end").collect()
+}
+
+fn skip_section<'a>(tokens: &mut impl Iterator<Item = &'a str>) {
+    for token in tokens {
+        if token == "$end" {
+            return;
+        }
+    }
+}
+
+// x and z are read as 0; only the lowest 64 bits are kept
+fn parse_bits(bits: &str) -> u64 {
+    bits.chars()
+        .fold(0, |value, bit| (value << 1) | (bit == '1') as u64)
+}
@@ -17,3 +17,23 @@ pub(crate) fn fallback_ordering<T, U: PartialOrd<T>>(
 
     None
 }
+
+// whether the name can be used as an identifier in assembly
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// the smallest number that is a multiple of both, or none if it doesn't fit in a u32; used to
+// combine alignments
+pub(crate) fn lcm(a: u32, b: u32) -> Option<u32> {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+
+    (a / x).checked_mul(b)
+}
@@ -0,0 +1,88 @@
+// helpers shared by the tests; each test uses only some of them
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ablomm_asm::{
+    assembly::Assembly,
+    emulator::Machine,
+    error::{Error, RecoveredError},
+    output::Segment,
+    src::Src,
+    target::Target,
+};
+use ariadne::Cache;
+use internment::Intern;
+
+pub fn messages(errors: &[Error], mut cache: impl Cache<Intern<Src>>) -> String {
+    let mut messages = Vec::new();
+    for error in errors {
+        let _ = error.write(&mut cache, &mut messages);
+    }
+
+    String::from_utf8_lossy(&messages).to_string()
+}
+
+pub fn example_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples")
+        .join(name)
+}
+
+// panics with the error messages if the file does not assemble
+pub fn assemble(src: &Path, target: &Target) -> Assembly {
+    match ablomm_asm::assemble_with_info(&src.to_string_lossy(), target) {
+        Ok(assembly) => assembly,
+        Err(RecoveredError(_, (errors, cache))) => panic!("{}", messages(&errors, cache)),
+    }
+}
+
+// writes the files of a small program to a directory of its own, as tests are run in parallel and
+// the assembler only reads from files; returns the path of the first file
+pub fn sources(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ablomm_test_{}_{}", test, std::process::id()));
+    fs::create_dir_all(&dir).expect("Could not create test directory");
+    for (name, src) in files {
+        fs::write(dir.join(name), src).expect("Could not write test file");
+    }
+
+    dir.join(files[0].0)
+}
+
+// the messages of the errors, or None if the file assembles
+pub fn errors(src: &Path) -> Option<String> {
+    match ablomm_asm::assemble_with_info(&src.to_string_lossy(), &Target::default()) {
+        Ok(_) => None,
+        Err(RecoveredError(_, (errors, cache))) => Some(messages(&errors, cache)),
+    }
+}
+
+// the example assembled for the simulator
+pub fn example(name: &str) -> Assembly {
+    assemble(&example_path(name), &Target::default())
+}
+
+// the machine code in ROM and the initialized data in RAM
+pub fn segments(assembly: &Assembly) -> Vec<Segment> {
+    let rom = Segment {
+        address: assembly.target.rom.start,
+        words: assembly.machine_code.clone(),
+    };
+    [&[rom][..], &assembly.ram].concat()
+}
+
+// a machine with the devices of the simulator, loaded with the segments
+pub fn machine(segments: &[Segment]) -> Machine {
+    let mut machine = Machine::with_devices(&Target::default())
+        .unwrap_or_else(|_| panic!("Could not emulate the target"));
+    for segment in segments {
+        machine
+            .load(segment)
+            .unwrap_or_else(|_| panic!("Could not load the assembly"));
+    }
+
+    machine
+}
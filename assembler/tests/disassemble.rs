@@ -0,0 +1,146 @@
+use std::fs;
+
+use ablomm_asm::{
+    disassembler::{self, Symbols},
+    error::RecoveredError,
+    output,
+    target::Target,
+};
+use common::{example, messages};
+
+mod common;
+
+// disassembles the machine code, then assembles the disassembly
+fn roundtrip(name: &str, machine_code: &[u32]) -> Vec<u32> {
+    let assembly = disassembler::disassemble(machine_code, &Symbols::default());
+
+    // the assembler only reads from files
+    let file =
+        std::env::temp_dir().join(format!("ablomm_disasm_{}_{}.asm", name, std::process::id()));
+    fs::write(&file, &assembly).expect("Could not write disassembly");
+
+    let reassembled = ablomm_asm::assemble_unchecked(&file.to_string_lossy(), &Target::default());
+    let _ = fs::remove_file(&file);
+
+    match reassembled {
+        Ok(reassembled) => reassembled,
+        Err(RecoveredError(_, (errors, cache))) => {
+            panic!("{}", messages(&errors, cache))
+        }
+    }
+}
+
+fn assert_roundtrip(name: &str, machine_code: &[u32]) {
+    let reassembled = roundtrip(name, machine_code);
+    assert_eq!(reassembled.len(), machine_code.len());
+
+    for (address, (original, new)) in machine_code.iter().zip(&reassembled).enumerate() {
+        assert_eq!(
+            original, new,
+            "0x{:04x}: expected {:08x}, but found {:08x}",
+            address, original, new
+        );
+    }
+}
+
+#[test]
+fn every_opcode_and_condition_roundtrips() {
+    // the operand bits, chosen to cover every register, positive and negative offsets, and the
+    // largest immediates and addresses
+    const OPERANDS: [u32; 8] = [
+        0x00000, 0xfffff, 0x12345, 0x5a5a5, 0xa5a5a, 0x0ff00, 0xf00ff, 0x07fff,
+    ];
+
+    let machine_code: Vec<u32> = (0..16)
+        .flat_map(|condition| (0..256).map(move |opcode| (condition << 28) | (opcode << 20)))
+        .flat_map(|word| OPERANDS.iter().map(move |operands| word | operands))
+        .collect();
+
+    assert_roundtrip("opcodes", &machine_code);
+}
+
+#[test]
+fn pseudorandom_words_roundtrip() {
+    // xorshift, so the words are the same every run
+    let mut state: u32 = 0x2545_f491;
+    let machine_code: Vec<u32> = (0..4000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        })
+        .collect();
+
+    assert_roundtrip("random", &machine_code);
+}
+
+#[test]
+fn examples_roundtrip() {
+    for name in [
+        "counter.asm",
+        "fib.asm",
+        "hello_world.asm",
+        "hello_world_char.asm",
+        "interrupts.asm",
+        "multiply.asm",
+        "primes.asm",
+    ] {
+        let machine_code = example(name).machine_code;
+        assert_roundtrip(name.trim_end_matches(".asm"), &machine_code);
+    }
+}
+
+#[test]
+fn instructions_are_written_as_assembly() {
+    let instructions = [
+        (0x00000000, "nop;"),
+        (0x00104046, "ld r0, *0x4046;"),
+        (0x00300008, "ld r0, 0x0008;"),
+        (0x00600000, "push r0;"),
+        (0x003e0001, "ld pc.link, L_0001;"),
+    ];
+
+    let machine_code: Vec<u32> = instructions.iter().map(|(word, _)| *word).collect();
+    let assembly = disassembler::disassemble(&machine_code, &Symbols::default());
+
+    for (word, instruction) in instructions {
+        assert!(
+            assembly
+                .lines()
+                .any(|line| line.trim_start().starts_with(instruction)),
+            "{:08x} was not disassembled as \"{}\":\n{}",
+            word,
+            instruction,
+            assembly
+        );
+    }
+}
+
+#[test]
+fn debug_info_maps_instructions_to_source() {
+    let mut assembly = example("counter.asm");
+
+    let debug_info = output::parse_debug_info(&output::to_debug_info(&mut assembly))
+        .unwrap_or_else(|_| panic!("Could not parse the debug information"));
+    let symbols = debug_info.symbols();
+
+    // `ld.ule pc, loop;` on line 15
+    let word = debug_info.word(4).expect("There is no word at 0x0004");
+    assert_eq!(word.location.line, 15);
+    assert!(
+        debug_info
+            .file(word)
+            .is_some_and(|file| file.ends_with("counter.asm"))
+    );
+    assert_eq!(
+        disassembler::disassemble_instruction(assembly.machine_code[4], &symbols).as_deref(),
+        Some("ld.ule pc, loop")
+    );
+    assert_eq!(
+        disassembler::disassemble_instruction(0x0e000000, &symbols),
+        None
+    );
+
+    assert!(output::parse_debug_info("{\"files\": []}").is_err());
+}
@@ -0,0 +1,425 @@
+use std::{cell::RefCell, fs, rc::Rc};
+
+use ablomm_asm::{
+    emulator::{
+        self, AccessKind, Campaign, Device, Fault, InterruptKind, Machine, Register, Stop, Tty,
+        Vcd, ZERO_BIT,
+    },
+    error::{Error, RecoveredError},
+    output::{Region, Segment},
+    target::Target,
+};
+use common::{example, machine, messages, segments};
+
+mod common;
+
+// the program is not checked against the target, so it can use devices that are added to the
+// machine; it is assembled from a temporary file, as the assembler only reads from files
+fn program(name: &str, src: &str) -> Machine {
+    let file = std::env::temp_dir().join(format!("ablomm_emu_{}_{}.asm", name, std::process::id()));
+    fs::write(&file, src).unwrap_or_else(|_| panic!("Could not write program"));
+    let machine_code = ablomm_asm::assemble_unchecked(&file.to_string_lossy(), &Target::default());
+    let _ = fs::remove_file(&file);
+
+    match machine_code {
+        Ok(machine_code) => machine(&[Segment {
+            address: 0,
+            words: machine_code,
+        }]),
+        Err(RecoveredError(_, (errors, cache))) => panic!("{}", messages(&errors, cache)),
+    }
+}
+
+fn run(machine: &mut Machine, max_cycles: u64) -> Stop {
+    machine.run_until(|machine| machine.cycles() >= max_cycles)
+}
+
+fn tty_output(machine: &Machine) -> String {
+    let tty = machine.device::<Tty>("tty").expect("There is no tty");
+    String::from_utf8_lossy(tty.output()).to_string()
+}
+
+#[test]
+fn examples_print_their_output() {
+    let examples = [
+        ("counter.asm", "0123456789\n"),
+        ("hello_world_char.asm", "Hello world!👻\n"),
+        ("hello_world.asm", "Hello world!👻\nHello world, again!😵\n"),
+        ("fib.asm", "0\n1\n1\n2\n3\n5\n8\n13\n21\n34\n"),
+        ("multiply.asm", "276\n"),
+    ];
+
+    for (name, output) in examples {
+        let mut machine = machine(&segments(&example(name)));
+        assert_eq!(run(&mut machine, 1_000_000), Stop::Shutdown, "{}", name);
+        assert_eq!(tty_output(&machine), output, "{}", name);
+    }
+}
+
+#[test]
+fn primes_are_printed() {
+    let mut machine = machine(&segments(&example("primes.asm")));
+    assert_eq!(run(&mut machine, 10_000_000), Stop::Shutdown);
+    assert!(tty_output(&machine).starts_with("2\n3\n5\n7\n11\n13\n"));
+}
+
+#[test]
+fn interrupts_are_taken() {
+    let mut machine = machine(&segments(&example("interrupts.asm")));
+
+    let interrupts = Rc::new(RefCell::new(Vec::new()));
+    let callback_interrupts = interrupts.clone();
+    machine.on_interrupt(move |interrupt| callback_interrupts.borrow_mut().push(interrupt.kind));
+
+    // the example waits for interrupts forever
+    assert_eq!(run(&mut machine, 100_000), Stop::Condition);
+    assert_eq!(
+        tty_output(&machine),
+        "got a software interrupt!\ngot an exception!\ngot a hardware interrupt!\n"
+    );
+    assert_eq!(
+        *interrupts.borrow(),
+        [
+            InterruptKind::Software,
+            InterruptKind::Exception,
+            InterruptKind::Hardware
+        ]
+    );
+}
+
+#[test]
+fn instructions_take_the_same_cycles_as_the_cpu() {
+    let mut machine = program(
+        "cycles",
+        r#"
+    ld r0, 1;            // ldi: fetch, decode, execute
+    add.s r0, r0, 0;     // alu
+    ld.eq r1, 2;         // not executed: fetch, decode
+    push r0;
+    pop r2;
+    ld r3, *(sp - 1);
+"#,
+    );
+
+    let cycles: Vec<_> = (0..6)
+        .map(|_| {
+            let start = machine.cycles();
+            machine.step();
+            machine.cycles() - start
+        })
+        .collect();
+
+    assert_eq!(cycles, [3, 3, 2, 3, 3, 3]);
+    assert_eq!(machine.register(Register::R0), 1);
+    assert_eq!(machine.register(Register::R1), 0);
+    assert_eq!(machine.register(Register::R2), 1);
+    assert_eq!(machine.register(Register::R3), 1);
+    assert_eq!(machine.register(Register::Sp), 0);
+    assert_eq!(machine.register(Register::Status) & ZERO_BIT, 0);
+}
+
+#[test]
+fn alu_sets_status() {
+    let mut machine = program(
+        "alu",
+        r#"
+    ld r0, 0xffff;
+    ld r1, 16;
+    shl r0, r0, r1;      // 0xffff0000
+    add.s r2, r0, r0;    // carry
+    sub.s r3, r1, r1;    // zero, and carry as there is no borrow
+    ld r4, 1;
+    ror.s r4, r4, 1;     // 0x80000000, carry
+    sub.s r5, 1, r1;     // negative, no carry
+    ld pc.link, end;
+end:
+"#,
+    );
+
+    let status = |machine: &Machine| machine.register(Register::Status) >> 2;
+    let mut statuses = Vec::new();
+    for _ in 0..9 {
+        machine.step();
+        statuses.push(status(&machine));
+    }
+
+    assert_eq!(machine.register(Register::R0), 0xffff0000);
+    assert_eq!(machine.register(Register::R2), 0xfffe0000);
+    assert_eq!(machine.register(Register::R3), 0);
+    assert_eq!(machine.register(Register::R4), 0x80000000);
+    assert_eq!(machine.register(Register::R5), 0xfffffff1);
+    // negative, zero, carry, overflow
+    assert_eq!(&statuses[3..8], [0b1010, 0b0110, 0b0110, 0b1010, 0b1000]);
+    assert_eq!(machine.register(Register::Lr), 9);
+    assert_eq!(machine.register(Register::Pc), 9);
+}
+
+// records every write, and raises an interrupt when 0 is written
+#[derive(Default)]
+struct Recorder {
+    writes: Vec<(u32, u32)>,
+    irq: bool,
+}
+
+impl Device for Recorder {
+    fn read(&mut self, offset: u32) -> u32 {
+        offset + 0x100
+    }
+
+    fn write(&mut self, offset: u32, value: u32) {
+        self.writes.push((offset, value));
+        self.irq = value == 0;
+    }
+
+    fn irq(&self) -> bool {
+        self.irq
+    }
+}
+
+#[test]
+fn devices_can_be_added() {
+    let mut machine = program(
+        "device",
+        r#"
+    ld pc, start;
+    ld pc, isr;
+start:
+    ld r0, status;
+    or r0, 0b10;
+    ld status, r0;       // enable interrupts
+    ld r0, *0x6001;
+    ld *0x6000, r0;
+    ld r0, 0;
+    ld *0x6002, r0;      // interrupt
+    ld pc, start;
+isr:
+    ld r1, *0x4004;      // the interrupt controller
+    ld *0x6003, r1;
+    ld r0, 0;
+    ld *0x4005, r0;      // shutdown
+"#,
+    );
+
+    let region = Region {
+        start: 0x6000,
+        depth: 4,
+    };
+    machine
+        .add_device("recorder", region, Some(3), Recorder::default())
+        .unwrap_or_else(|_| panic!("Could not add the device"));
+    assert!(
+        machine
+            .add_device("recorder", region, None, Recorder::default())
+            .is_err()
+    );
+
+    let accesses = Rc::new(RefCell::new(Vec::new()));
+    let callback_accesses = accesses.clone();
+    machine.on_memory_access(move |access| {
+        if access.kind != AccessKind::Fetch {
+            callback_accesses
+                .borrow_mut()
+                .push((access.kind, access.address, access.value));
+        }
+    });
+
+    assert_eq!(run(&mut machine, 1000), Stop::Shutdown);
+
+    let recorder = machine
+        .device::<Recorder>("recorder")
+        .expect("There is no recorder");
+    assert_eq!(recorder.writes, [(0, 0x101), (2, 0), (3, 1 << 3)]);
+    assert!(
+        accesses
+            .borrow()
+            .contains(&(AccessKind::Read, 0x6001, 0x101))
+    );
+}
+
+#[test]
+fn memory_can_be_read_and_written() {
+    let mut machine = program(
+        "memory",
+        r#"
+    ld r0, *0x8000;
+    add r0, 1;
+    ld *0x8001, r0;
+    ld r0, 0;
+    ld *0x4005, r0;
+"#,
+    );
+
+    machine
+        .write_memory(0x8000, 41)
+        .unwrap_or_else(|_| panic!("Could not write RAM"));
+    assert!(machine.write_memory(0x4006, 0).is_err());
+
+    let mut executed = Vec::new();
+    let stop = machine.run_until(|machine| {
+        executed.push(machine.register(Register::Pc));
+        false
+    });
+
+    assert_eq!(stop, Stop::Shutdown);
+    assert_eq!(executed, [0, 1, 2, 3, 4]);
+    assert_eq!(machine.read_memory(0x8001), Some(42));
+    assert_eq!(machine.read_memory(0x0000), Some(0x00108000));
+    assert_eq!(machine.read_memory(0x4006), None);
+}
+
+#[test]
+fn memory_files_are_parsed() {
+    let ram = Target::default().ram;
+    let segments = emulator::parse_memory("1 2 // comment\n@10\n3", ram)
+        .unwrap_or_else(|_| panic!("Could not parse the memory"));
+    assert_eq!(
+        segments,
+        [
+            Segment {
+                address: ram.start,
+                words: vec![1, 2]
+            },
+            Segment {
+                address: ram.start + 0x10,
+                words: vec![3]
+            }
+        ]
+    );
+
+    // the words would be past the end of the address space
+    for memory in ["@ffffffff 1", "@7fff 1 2"] {
+        match emulator::parse_memory(memory, ram) {
+            Err(Error::Bare(message)) => assert!(message.contains("are not in"), "{}", message),
+            _ => panic!("{} was parsed", memory),
+        }
+    }
+    assert!(
+        emulator::parse_memory(
+            "1",
+            Region {
+                start: u32::MAX,
+                depth: 0
+            }
+        )
+        .is_err()
+    );
+
+    let mut machine = machine(&[]);
+    let segment = Segment {
+        address: u32::MAX,
+        words: vec![1, 2],
+    };
+    assert!(machine.load(&segment).is_err());
+    assert_eq!(segment.to_string(), "0xffffffff to 0x100000000");
+}
+
+#[test]
+fn vcd_has_every_cycle() {
+    let mut machine = machine(&segments(&example("counter.asm")));
+    let mut vcd = Vcd::new(Vec::new(), &machine).unwrap_or_else(|_| panic!("Could not write"));
+    while !machine.is_shutdown() {
+        vcd.cycle(&mut machine)
+            .unwrap_or_else(|_| panic!("Could not write"));
+    }
+    let vcd = vcd
+        .finish(&machine)
+        .unwrap_or_else(|_| panic!("Could not write"));
+    let vcd = String::from_utf8_lossy(&vcd);
+
+    for var in [
+        "$var wire 1 ! clk $end",
+        "$var wire 32 # addr [31:0] $end",
+        "$var wire 1 ( timer_int $end",
+        "$scope module g_registers[10] $end",
+    ] {
+        assert!(vcd.contains(var), "{} is not declared", var);
+    }
+
+    // the clock starts at 0, then rises for every cycle, and falls after every rising edge but the last
+    let edges = |edge| vcd.lines().filter(|line| *line == edge).count() as u64;
+    assert_eq!(edges("1!"), machine.cycles());
+    assert_eq!(edges("0!"), machine.cycles());
+    assert!(vcd.contains(&format!("#{}\n", 20 * machine.cycles() - 10)));
+}
+
+#[test]
+fn faults_are_injected() {
+    let campaign = Campaign::parse(
+        r#"
+expected_output = "0123456789\n"
+
+[[faults]]
+kind = "flip_register"
+cycle = 20
+register = "r0"
+bit = 1
+
+[[faults]]
+kind = "corrupt_read"
+end = 0
+address = 0
+mask = 1
+"#,
+    )
+    .unwrap_or_else(|_| panic!("Could not parse the faults"));
+
+    let mut machine = machine(&segments(&example("counter.asm")));
+    for fault in campaign.faults {
+        machine
+            .add_fault(fault)
+            .unwrap_or_else(|_| panic!("Could not add the fault"));
+    }
+
+    assert_eq!(run(&mut machine, 10_000), Stop::Shutdown);
+    // '0' is read as '1', then '3' is flipped to '1' so the count goes back
+    assert_eq!(tty_output(&machine), "12123456789\n");
+
+    let injections: Vec<_> = machine
+        .injections()
+        .iter()
+        .map(|injection| (injection.cycle, injection.fault))
+        .collect();
+    assert_eq!(injections, [(0, 1), (20, 0)]);
+}
+
+#[test]
+fn interrupts_can_be_dropped() {
+    let mut machine = machine(&segments(&example("interrupts.asm")));
+    machine
+        .add_fault(Fault::DropInterrupts {
+            start: 0,
+            end: None,
+        })
+        .unwrap_or_else(|_| panic!("Could not add the fault"));
+
+    assert_eq!(run(&mut machine, 100_000), Stop::Condition);
+    assert_eq!(
+        tty_output(&machine),
+        "got a software interrupt!\ngot an exception!\n"
+    );
+    assert!(!machine.injections().is_empty());
+}
+
+#[test]
+fn faults_are_checked() {
+    let mut machine = machine(&segments(&example("counter.asm")));
+    let flip_register = Fault::FlipRegister {
+        cycle: 0,
+        register: Register::R0,
+        bit: 32,
+    };
+    let flip_device = Fault::FlipMemory {
+        cycle: 0,
+        address: 0x4000,
+        bit: 0,
+    };
+
+    assert!(machine.add_fault(flip_register).is_err());
+    assert!(machine.add_fault(flip_device).is_err());
+    assert!(
+        Campaign::parse(
+            "[[faults]]\nkind = \"flip_register\"\ncycle = 0\nregister = \"r11\"\nbit = 0"
+        )
+        .is_err()
+    );
+}
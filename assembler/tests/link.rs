@@ -0,0 +1,263 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ablomm_asm::{
+    SrcCache,
+    error::{Error, RecoveredError},
+    object::{self, Linked, Object},
+    target::Target,
+};
+use common::{assemble, example_path, messages};
+
+mod common;
+
+// a directory for the files of a single test, as tests are run in parallel
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ablomm_asm_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).expect("Could not create test directory");
+    dir
+}
+
+fn assemble_object(src: &Path, target: &Target) -> Object {
+    match ablomm_asm::assemble_object(&src.to_string_lossy(), target) {
+        // written and parsed again, so that the object format is also checked
+        Ok(object) => Object::parse(&object.to_toml(), &object.name)
+            .unwrap_or_else(|_| panic!("Could not parse the object of {}", src.display())),
+        Err(RecoveredError(_, (errors, cache))) => panic!("{}", messages(&errors, cache)),
+    }
+}
+
+// the objects in the order the assembler places the files (i.e. depth first from the root file)
+fn link(srcs: &[PathBuf], target: &Target) -> Linked {
+    let objects: Vec<_> = srcs
+        .iter()
+        .map(|src| assemble_object(src, target))
+        .collect();
+
+    match object::link(&objects, target) {
+        Ok(linked) => linked,
+        Err(errors) => panic!(
+            "{}",
+            messages(&errors, SrcCache::new(|_| Ok(String::new())))
+        ),
+    }
+}
+
+fn link_errors(objects: &[&str]) -> Vec<String> {
+    let objects: Vec<_> = objects
+        .iter()
+        .enumerate()
+        .map(|(index, object)| {
+            Object::parse(object, &format!("{}.o", index))
+                .unwrap_or_else(|_| panic!("Could not parse object {}", index))
+        })
+        .collect();
+
+    match object::link(&objects, &Target::default()) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors
+            .into_iter()
+            .map(|error| match error {
+                Error::Bare(message) => message,
+                _ => panic!("Linker errors are not spanned"),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn linked_examples_match_assembled_examples() {
+    let target = Target::default();
+    let examples = [
+        ("counter.asm", &[][..]),
+        ("hello_world_char.asm", &[][..]),
+        ("hello_world.asm", &["lib/print.asm", "lib/num.asm"][..]),
+        ("fib.asm", &["lib/print.asm", "lib/num.asm"][..]),
+        ("interrupts.asm", &["lib/print.asm", "lib/num.asm"][..]),
+        ("multiply.asm", &["lib/print.asm", "lib/num.asm"][..]),
+    ];
+
+    for (root, libs) in examples {
+        let srcs: Vec<_> = std::iter::once(root)
+            .chain(libs.iter().copied())
+            .map(example_path)
+            .collect();
+
+        let linked = link(&srcs, &target);
+        assert_eq!(
+            linked.machine_code,
+            assemble(&srcs[0], &target).machine_code,
+            "{} was linked differently than it was assembled",
+            root
+        );
+    }
+}
+
+#[test]
+fn linked_data_and_startup_match_assembled() {
+    let dir = test_dir("link_data");
+    let main = dir.join("main.asm");
+    let lib = dir.join("lib.asm");
+
+    let main_src = r#"
+import counter, bump, table from "lib.asm";
+import * from "target";
+
+startup main;
+
+section data;
+greeting: 0x1234;
+greeting_end: greeting + 1;
+
+section text;
+main: {
+    ld r0, counter;
+    ld r1, *counter;
+    add r1, bump;
+    ld *counter, r1;
+    ld r2, table;
+    ld r3, *greeting;
+    add r3, greeting_end - greeting;
+    ld pc, main;
+}
+"#;
+    let lib_src = r#"
+section data;
+export counter: 7;
+export bump = 3;
+
+section rodata;
+align 4;
+export table: 1; 2; 3; table + 1;
+
+section bss;
+scratch: reserve 5;
+
+section data;
+ptr: scratch;
+"#;
+    fs::write(&main, main_src).expect("Could not write main.asm");
+    fs::write(&lib, lib_src).expect("Could not write lib.asm");
+
+    let target = Target::default();
+    let assembly = assemble(&main, &target);
+    let linked = link(&[main, lib], &target);
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(linked.machine_code, assembly.machine_code);
+    assert_eq!(linked.ram, assembly.ram);
+    assert_eq!(linked.load_image, assembly.load_image);
+}
+
+#[test]
+fn objects_can_import_files_that_do_not_exist() {
+    let dir = test_dir("link_missing");
+    let main = dir.join("main.asm");
+    fs::write(
+        &main,
+        "import helper from \"prebuilt.asm\";\nld pc.link, helper;\n",
+    )
+    .expect("Could not write main.asm");
+
+    let object = assemble_object(&main, &Target::default());
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(object.relocations.len(), 1);
+    assert_eq!(object.relocations[0].value.to_string(), "helper + 0x0");
+}
+
+const HELPER: &str = r#"
+name = "helper.asm"
+rom = [0x003e0000]
+
+[[exports]]
+name = "helper"
+value = ".rom + 0x0"
+
+[[exports]]
+name = "big"
+value = "0x100"
+"#;
+
+#[test]
+fn duplicate_export() {
+    let other = HELPER.replace("helper.asm", "other.asm");
+    let errors = link_errors(&[HELPER, &other]);
+    assert_eq!(
+        errors,
+        [
+            "The symbol \"helper\" is exported by both \"helper.asm\" and \"other.asm\"",
+            "The symbol \"big\" is exported by both \"helper.asm\" and \"other.asm\"",
+        ]
+    );
+}
+
+#[test]
+fn missing_symbol() {
+    let main = r#"
+name = "main.asm"
+rom = [0x00300000]
+
+[[relocations]]
+memory = "rom"
+offset = 0
+kind = "immediate"
+value = "missing + 0x0"
+"#;
+
+    let errors = link_errors(&[main, HELPER]);
+    assert_eq!(
+        errors,
+        ["The symbol \"missing\" imported by \"main.asm\" is not exported by any of the objects"]
+    );
+}
+
+#[test]
+fn alu_immediate_does_not_fit() {
+    // add r0, big;
+    let main = r#"
+name = "main.asm"
+rom = [0x0f580000]
+
+[[relocations]]
+memory = "rom"
+offset = 0
+kind = "alu_immediate"
+value = "big + 0x0"
+"#;
+
+    let errors = link_errors(&[main, HELPER]);
+    assert_eq!(
+        errors,
+        [
+            "The 8-bit alu immediate of the word at 0x0000 in \"main.asm\" is 0x100, which does not fit in it"
+        ]
+    );
+}
+
+#[test]
+fn relocated_fields_are_set() {
+    // ld r0, helper + 2; placed after main, so helper is at 0x0001
+    let main = r#"
+name = "main.asm"
+rom = [0x00300000]
+
+[[relocations]]
+memory = "rom"
+offset = 0
+kind = "immediate"
+value = "helper + 0x2"
+"#;
+
+    let objects = [
+        Object::parse(main, "main.o").unwrap_or_else(|_| panic!("Invalid main object")),
+        Object::parse(HELPER, "helper.o").unwrap_or_else(|_| panic!("Invalid helper object")),
+    ];
+    let Ok(linked) = object::link(&objects, &Target::default()) else {
+        panic!("Could not link objects");
+    };
+
+    assert_eq!(linked.machine_code, [0x00300003, 0x003e0000]);
+}
@@ -0,0 +1,146 @@
+use ablomm_asm::{
+    error::Error,
+    output::{self, Endianness, Segment},
+    target::Target,
+};
+
+#[test]
+fn ihex_and_srec_start_at_the_rom() {
+    let machine_code = [0x01020304];
+
+    assert_eq!(
+        output::to_ihex(&machine_code, 0x10, Endianness::Big),
+        ":0400400001020304B2\n:00000001FF\n"
+    );
+    assert_eq!(
+        output::to_srec(&machine_code, 0x10, Endianness::Big),
+        "S0030000FC\nS107004001020304AE\nS5030001FB\nS9030000FC\n"
+    );
+
+    // byte 0x10000 needs the upper 16 bits of the address, and a 3 byte address
+    assert_eq!(
+        output::to_ihex(&machine_code, 0x4000, Endianness::Big),
+        ":020000040001F9\n:0400000001020304F2\n:00000001FF\n"
+    );
+    assert!(
+        output::to_srec(&machine_code, 0x4000, Endianness::Big)
+            .lines()
+            .any(|line| line == "S20801000001020304EC")
+    );
+}
+
+#[test]
+fn bin_words_have_the_given_endianness() {
+    let machine_code = [0x01020304, 0xa0b0c0d0];
+
+    assert_eq!(
+        output::to_bin(&machine_code, Endianness::Big),
+        [0x01, 0x02, 0x03, 0x04, 0xa0, 0xb0, 0xc0, 0xd0]
+    );
+    assert_eq!(
+        output::to_bin(&machine_code, Endianness::Little),
+        [0x04, 0x03, 0x02, 0x01, 0xd0, 0xc0, 0xb0, 0xa0]
+    );
+    assert!(output::to_bin(&[], Endianness::Big).is_empty());
+}
+
+#[test]
+fn ihex_and_srec_records_have_at_most_16_bytes() {
+    let machine_code = [0x00300008, 0x11111111, 0x22222222, 0x33333333, 0xdeadbeef];
+
+    assert_eq!(
+        output::to_ihex(&machine_code, 0, Endianness::Little),
+        ":100000000800300011111111222222223333333320\n\
+         :04001000EFBEADDEB4\n\
+         :00000001FF\n"
+    );
+    assert_eq!(
+        output::to_srec(&machine_code, 0, Endianness::Little),
+        "S0030000FC\n\
+         S1130000080030001111111122222222333333331C\n\
+         S1070010EFBEADDEB0\n\
+         S5030002FA\n\
+         S9030000FC\n"
+    );
+}
+
+#[test]
+fn mif_and_coe_fill_the_memory() {
+    let machine_code = [0x00300008, 0xdeadbeef];
+
+    assert_eq!(
+        output::to_mif(&machine_code, 4).unwrap_or_else(|_| panic!("Could not write the MIF")),
+        "DEPTH = 4;\nWIDTH = 32;\nADDRESS_RADIX = HEX;\nDATA_RADIX = HEX;\nCONTENT\nBEGIN\n\
+         0000 : 00300008;\n0001 : DEADBEEF;\n[0002..0003] : 00000000;\nEND;\n"
+    );
+    assert!(
+        output::to_mif(&machine_code, 3)
+            .unwrap_or_else(|_| panic!("Could not write the MIF"))
+            .ends_with("0001 : DEADBEEF;\n0002 : 00000000;\nEND;\n")
+    );
+    assert!(
+        output::to_mif(&machine_code, 2)
+            .unwrap_or_else(|_| panic!("Could not write the MIF"))
+            .ends_with("0001 : DEADBEEF;\nEND;\n")
+    );
+
+    assert_eq!(
+        output::to_coe(&machine_code, 3).unwrap_or_else(|_| panic!("Could not write the COE")),
+        "memory_initialization_radix=16;\nmemory_initialization_vector=\n\
+         00300008,\ndeadbeef,\n00000000;\n"
+    );
+}
+
+#[test]
+fn mif_and_coe_must_fit_in_the_memory() {
+    let machine_code = [0x00300008, 0xdeadbeef];
+
+    for depth in [0, 1] {
+        match output::to_mif(&machine_code, depth) {
+            Err(Error::Bare(message)) => assert!(message.contains("does not fit"), "{}", message),
+            _ => panic!("The MIF was written with a depth of {}", depth),
+        }
+        match output::to_coe(&machine_code, depth) {
+            Err(Error::Bare(message)) => assert!(message.contains("does not fit"), "{}", message),
+            _ => panic!("The COE was written with a depth of {}", depth),
+        }
+    }
+}
+
+#[test]
+fn vmem_splits_rom_and_ram() {
+    let target = Target::default();
+    let segment = |address, words: &[u32]| Segment {
+        address,
+        words: words.to_vec(),
+    };
+
+    let vmem = output::to_vmem(
+        &[
+            segment(0x0000, &[0x00300008, 0x1]),
+            segment(0x0010, &[0x2]),
+            segment(0x8004, &[0xdeadbeef]),
+            segment(0x8010, &[]),
+        ],
+        &target,
+    )
+    .unwrap_or_else(|_| panic!("Could not write the vmem"));
+
+    // the addresses are relative to the start of each memory
+    assert_eq!(vmem.rom, "@0000\n00300008\n00000001\n@0010\n00000002\n");
+    assert_eq!(vmem.ram.as_deref(), Some("@0004\ndeadbeef\n"));
+
+    let vmem = output::to_vmem(&[segment(0x0000, &[0x1])], &target)
+        .unwrap_or_else(|_| panic!("Could not write the vmem"));
+    assert_eq!(vmem.ram, None);
+
+    // the word at 0x4000 is a device
+    match output::to_vmem(&[segment(0x3fff, &[0x1, 0x2])], &target) {
+        Err(Error::Bare(message)) => assert!(
+            message.starts_with("Words at 0x3fff to 0x4000 are not entirely in ROM"),
+            "{}",
+            message
+        ),
+        _ => panic!("The vmem was written"),
+    }
+}
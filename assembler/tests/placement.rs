@@ -0,0 +1,130 @@
+use ablomm_asm::{assembly::Assembly, emulator::Register, output::Segment, target::Target};
+use common::{assemble, errors, machine, segments, sources};
+
+mod common;
+
+fn program(test: &str, src: &str) -> Assembly {
+    assemble(&sources(test, &[("main.asm", src)]), &Target::default())
+}
+
+#[test]
+fn org_and_align_fill_the_gap() {
+    let assembly = program(
+        "org_align_fill",
+        "1;\norg 4, 0xffffffff;\n2;\nalign 8;\n3;\nalign 1, 0xffffffff;\n4;\n",
+    );
+
+    assert_eq!(
+        assembly.machine_code,
+        [1, 0xffffffff, 0xffffffff, 0xffffffff, 2, 0, 0, 0, 3, 4]
+    );
+}
+
+#[test]
+fn org_can_not_go_backwards() {
+    let src = sources("org_backwards", &[("main.asm", "1;\n2;\n3;\norg 2;\n")]);
+
+    let errors = errors(&src).expect("The origin before the current address assembled");
+
+    assert!(
+        errors.contains("Origin before current address"),
+        "{}",
+        errors
+    );
+}
+
+#[test]
+fn sections_are_placed_in_their_memory() {
+    let src = sources(
+        "sections",
+        &[
+            (
+                "main.asm",
+                "import * from \"lib.asm\";\n1;\nsection data;\n2;\nsection rodata;\n3;\nsection data;\n4;\nsection text;\n5;\n{\n    section data;\n    6;\n}\n7;\n",
+            ),
+            // each file starts in the text section
+            ("lib.asm", "8;\nsection data;\n9;\n"),
+        ],
+    );
+    let assembly = assemble(&src, &Target::default());
+
+    assert_eq!(assembly.machine_code, [1, 3, 5, 7, 8]);
+    assert_eq!(
+        assembly.ram,
+        [Segment {
+            address: 0x8000,
+            words: vec![2, 4, 6, 9],
+        }]
+    );
+}
+
+#[test]
+fn bss_can_not_have_words() {
+    let src = sources("bss_words", &[("main.asm", "section bss;\n1;\n")]);
+    let errors = errors(&src).expect("The words in the bss section assembled");
+
+    assert!(errors.contains("Words in bss section"), "{}", errors);
+}
+
+#[test]
+fn reserve_skips_words_in_ram() {
+    let assembly = program(
+        "reserve",
+        "section bss;\ncounter: reserve 1;\nalign 4;\nbuffer: reserve 16;\nbuffer_end:\nsection data;\n1;\nreserve 2;\n2;\n",
+    );
+    let address = |name: &str| {
+        assembly
+            .symbols
+            .iter()
+            .find(|symbol| symbol.name == name)
+            .and_then(|symbol| symbol.number)
+    };
+
+    assert_eq!(address("counter"), Some(0x8000));
+    assert_eq!(address("buffer"), Some(0x8004));
+    assert_eq!(address("buffer_end"), Some(0x8014));
+    assert!(assembly.machine_code.is_empty());
+
+    // the reserved words in the data section split it into segments
+    assert_eq!(
+        assembly.ram,
+        [
+            Segment {
+                address: 0x8014,
+                words: vec![1],
+            },
+            Segment {
+                address: 0x8017,
+                words: vec![2],
+            },
+        ]
+    );
+}
+
+#[test]
+fn reserve_can_not_be_in_rom() {
+    let src = sources("reserve_in_rom", &[("main.asm", "reserve 1;\n")]);
+    let errors = errors(&src).expect("The reserve in ROM assembled");
+
+    assert!(errors.contains("Reserved words in ROM"), "{}", errors);
+}
+
+#[test]
+fn startup_copies_the_data_section_to_ram() {
+    let assembly = program(
+        "startup",
+        "startup main;\nsection data;\nfirst: 10;\nreserve 1;\nlast: 20;\nsection text;\nmain:\n    ld r0, *first;\n    ld r1, *last;\nend: ld pc, end;\n",
+    );
+
+    // only the ROM is loaded, as the startup code initializes the RAM
+    let mut machine = machine(&segments(&assembly)[..1]);
+    machine
+        .write_memory(0x8001, 0xdeadbeef)
+        .unwrap_or_else(|_| panic!("Could not write to RAM"));
+    machine.run_until(|machine| machine.instructions() >= 1000);
+
+    assert_eq!(machine.register(Register::R0), 10);
+    assert_eq!(machine.register(Register::R1), 20);
+    // reserved words between the initialized words are copied as 0
+    assert_eq!(machine.read_memory(0x8001), Some(0));
+}
@@ -0,0 +1,53 @@
+use common::{errors, sources};
+
+mod common;
+
+#[test]
+fn stores_to_rom_are_errors() {
+    let src = sources(
+        "store_to_rom",
+        &[("main.asm", "ld *0x0001, r0;\nld pc, pc;\n")],
+    );
+    let errors = errors(&src).expect("The store to ROM assembled");
+
+    assert!(errors.contains("Store to ROM"), "{}", errors);
+    assert!(errors.contains("can not be written"), "{}", errors);
+}
+
+#[test]
+fn loads_from_rom_and_stores_to_ram_and_devices_assemble() {
+    let src = sources(
+        "load_from_rom",
+        &[(
+            "main.asm",
+            "ld r0, *0x0001;\nld *0x8000, r0;\nld *0x4006, r0;\nld pc, pc;\n",
+        )],
+    );
+
+    assert_eq!(errors(&src), None);
+}
+
+#[test]
+fn unmapped_addresses_are_errors() {
+    let src = sources(
+        "unmapped_address",
+        &[("main.asm", "ld r0, *0x5000;\nld pc, pc;\n")],
+    );
+    let errors = errors(&src).expect("The unmapped load assembled");
+
+    assert!(errors.contains("Unmapped address"), "{}", errors);
+}
+
+#[test]
+fn words_outside_of_ram_are_errors() {
+    let src = sources(
+        "outside_of_ram",
+        &[(
+            "main.asm",
+            "ld pc, pc;\nsection data;\norg 0xffff;\n1;\n2;\n",
+        )],
+    );
+    let errors = errors(&src).expect("The words outside of RAM assembled");
+
+    assert!(errors.contains("Words outside of RAM"), "{}", errors);
+}
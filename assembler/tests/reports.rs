@@ -0,0 +1,120 @@
+use ablomm_asm::{disassembler, error::Error, output, target::Target};
+use common::{assemble, example, sources};
+
+mod common;
+
+#[test]
+fn listing_skips_files_without_words() {
+    let listing = output::to_listing(&mut example("hello_world.asm"));
+    let files: Vec<_> = listing
+        .lines()
+        .filter(|line| line.ends_with(".asm:"))
+        .collect();
+
+    assert_eq!(files.len(), 3, "{}", listing);
+    assert!(files[0].ends_with("hello_world.asm:"));
+    assert!(!listing.contains("<target>"));
+    assert!(listing.lines().any(|line| {
+        line.starts_with("0000  00300008  cond=none op=LDI a=r0 immediate=0x0008")
+            && line.ends_with("9:2     ld r0, string1;")
+    }));
+}
+
+#[test]
+fn size_report_skips_files_without_words() {
+    let report = output::to_size_report(&example("primes.asm"));
+
+    assert!(report.starts_with("ROM usage: 142 of 16384 words (0.87%)"));
+    assert!(!report.contains("<target>"), "{}", report);
+    assert!(report.contains("      43   30.28%  0x0000    "));
+}
+
+#[test]
+fn c_header_defines_exported_symbols() {
+    let header = output::to_c_header(&mut example("hello_world.asm"), "HELLO_WORLD_H")
+        .unwrap_or_else(|_| panic!("Could not write the header"));
+
+    assert!(header.starts_with("#ifndef HELLO_WORLD_H\n#define HELLO_WORLD_H\n\n"));
+    assert!(header.contains("#define PRINT 0x00000014u // print (address)\n"));
+    assert!(header.contains("#define TTY 0x00004006u // tty (indirect)\n"));
+    assert!(header.ends_with("\n\n#endif // HELLO_WORLD_H\n"));
+}
+
+#[test]
+fn constants_with_the_same_name_are_an_error() {
+    let src = sources(
+        "constants_same_name",
+        &[
+            (
+                "main.asm",
+                "import * from \"lib.asm\";\nexport foo = 1;\nld pc, pc;\n",
+            ),
+            ("lib.asm", "export FOO = 2;\n"),
+        ],
+    );
+    let mut assembly = assemble(&src, &Target::default());
+
+    match output::to_rust_constants(&mut assembly) {
+        Err(Error::Bare(message)) => assert!(
+            message.contains("have the same constant name \"FOO\""),
+            "{}",
+            message
+        ),
+        _ => panic!("The constants were written"),
+    }
+}
+
+#[test]
+fn map_is_a_symbol_file() {
+    let src = sources(
+        "map_symbols",
+        &[(
+            "main.asm",
+            "a_label_with_a_name_longer_than_its_column: ld pc, a_label_with_a_name_longer_than_its_column;\nexport device_register_with_a_long_name = *0x12345678;\nnumber = 0x87654321;\n",
+        )],
+    );
+    let map = output::to_map(&mut assemble(&src, &Target::default()));
+    let symbols = disassembler::parse_symbols(&map)
+        .unwrap_or_else(|_| panic!("Could not parse the map:\n{}", map));
+
+    // numbers are not addresses, so they are not symbols
+    assert_eq!(
+        symbols.iter().collect::<Vec<_>>(),
+        [
+            (0x0000, "a_label_with_a_name_longer_than_its_column"),
+            (0x12345678, "device_register_with_a_long_name"),
+        ],
+        "{}",
+        map
+    );
+}
+
+#[test]
+fn debug_info_is_read_back() {
+    let json = output::to_debug_info(&mut example("hello_world.asm"));
+    let debug_info = output::parse_debug_info(&json)
+        .unwrap_or_else(|_| panic!("Could not parse the debug information:\n{}", json));
+
+    let word = debug_info.word(0).expect("There is no word at 0");
+    assert!(debug_info.file(word).unwrap().ends_with("hello_world.asm"));
+    assert_eq!((word.location.line, word.location.column), (9, 2));
+
+    let scope = &debug_info.scopes[0];
+    assert_eq!((scope.name.as_deref(), scope.depth), (None, 0));
+    assert_eq!((scope.location.start, scope.location.line), (0, 1));
+
+    let string1 = debug_info
+        .symbols
+        .iter()
+        .find(|symbol| symbol.name == "string1")
+        .expect("There is no symbol string1");
+    assert_eq!(
+        (
+            string1.kind.as_str(),
+            string1.value.as_str(),
+            string1.number
+        ),
+        ("address", "0x0008", Some(8))
+    );
+    assert_eq!((string1.location.line, string1.location.column), (22, 1));
+}
@@ -0,0 +1,11 @@
+use ablomm_asm::target::Target;
+
+#[test]
+fn module_exports_devices_and_status_bits() {
+    let module = Target::default().module();
+
+    assert!(module.starts_with("// generated from the target \""));
+    assert!(module.contains("\n// status register bitmasks\nexport negative_bit = 1 << 5;\n"));
+    assert!(module.contains("\nexport tty = *0x4006;\n"));
+    assert!(module.contains("\nexport power_restart_code = 1;\n"));
+}
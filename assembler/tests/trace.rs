@@ -0,0 +1,150 @@
+use std::{cell::RefCell, rc::Rc};
+
+use ablomm_asm::{
+    emulator::{Machine, Vcd},
+    output::{self, DebugInfo},
+    trace::{self, Fetch},
+};
+use common::{example, machine, segments};
+
+mod common;
+
+// a machine running the example, and the debug information of the example
+fn example_machine(name: &str) -> (Machine, DebugInfo) {
+    let mut assembly = example(name);
+
+    let mut debug_info = output::parse_debug_info(&output::to_debug_info(&mut assembly))
+        .unwrap_or_else(|_| panic!("Could not parse the debug information"));
+    debug_info.read_sources();
+
+    (machine(&segments(&assembly)), debug_info)
+}
+
+// the value change dump of the machine, and the instructions it executed; an instruction is fetched
+// in the cycle before it is decoded
+fn dump(mut machine: Machine, max_cycles: u64) -> (String, Vec<Fetch>) {
+    let executed = Rc::new(RefCell::new(Vec::new()));
+    let callback_executed = executed.clone();
+    machine.on_execute(move |execute| {
+        callback_executed.borrow_mut().push(Fetch {
+            cycle: execute.cycle - 1,
+            time: 20 * (execute.cycle - 1) + 10,
+            address: execute.address,
+            word: execute.word,
+        })
+    });
+
+    let mut vcd = Vcd::new(Vec::new(), &machine).unwrap_or_else(|_| panic!("Could not write"));
+    while !machine.is_shutdown() && machine.cycles() < max_cycles {
+        vcd.cycle(&mut machine)
+            .unwrap_or_else(|_| panic!("Could not write"));
+    }
+    let vcd = vcd
+        .finish(&machine)
+        .unwrap_or_else(|_| panic!("Could not write"));
+
+    let executed = executed.borrow().clone();
+    (String::from_utf8_lossy(&vcd).to_string(), executed)
+}
+
+#[test]
+fn fetches_are_the_executed_instructions() {
+    for (name, max_cycles) in [("counter.asm", u64::MAX), ("interrupts.asm", 20_000)] {
+        let (machine, _) = example_machine(name);
+        let (vcd, executed) = dump(machine, max_cycles);
+
+        let fetches =
+            trace::parse_fetches(&vcd).unwrap_or_else(|_| panic!("Could not read the dump"));
+        assert!(!fetches.is_empty(), "{}", name);
+        assert_eq!(fetches, executed, "{}", name);
+    }
+}
+
+#[test]
+fn fetches_are_annotated() {
+    let (machine, debug_info) = example_machine("counter.asm");
+    let (vcd, _) = dump(machine, u64::MAX);
+
+    let fetches = trace::parse_fetches(&vcd).unwrap_or_else(|_| panic!("Could not read the dump"));
+    let trace = trace::annotate(&fetches, &debug_info);
+    let lines: Vec<_> = trace.lines().collect();
+
+    assert_eq!(lines.len(), fetches.len());
+    assert!(lines[1].starts_with("       3  0x0001  00404006  ld *tty, r0"));
+    assert!(lines[1].contains("counter.asm:12  ld tty, num;"));
+    assert!(lines[4].contains("ld.ule pc, loop"));
+}
+
+// the layout of a dump written by Verilator, which has a TOP scope and ld_ir, and starts with
+// unknown values
+#[test]
+fn simulator_dumps_are_read() {
+    let vcd = r#"
+$version Generated by VerilatedVcd $end
+$timescale 1ps $end
+$scope module TOP $end
+ $scope module simulator $end
+  $var wire 1 # clk $end
+  $var wire 32 $ addr [31:0] $end
+  $var wire 32 % data [31:0] $end
+  $var wire 1 & rd $end
+  $scope module cpu0 $end
+   $var wire 1 ' ld_ir $end
+  $upscope $end
+ $upscope $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+0#
+bx $
+bz %
+x&
+0'
+$end
+#5
+b0 $
+b1100000000000000110000 %
+1&
+1'
+$comment the first fetch $end
+#10
+1#
+#20
+0#
+b101 $
+0&
+0'
+#30
+1#
+#40
+0#
+b101 $
+b1111 %
+1&
+1'
+#50
+1#
+"#;
+
+    let fetches = trace::parse_fetches(vcd).unwrap_or_else(|_| panic!("Could not read the dump"));
+    assert_eq!(
+        fetches,
+        [
+            Fetch {
+                cycle: 0,
+                time: 10,
+                address: 0,
+                word: 0x00300030
+            },
+            Fetch {
+                cycle: 2,
+                time: 50,
+                address: 5,
+                word: 0xf
+            }
+        ]
+    );
+
+    assert!(trace::parse_fetches("$scope module simulator $end $enddefinitions $end").is_err());
+}
@@ -18,4 +18,11 @@ module mem #(
   always_ff @(posedge clk) begin
     if (en && wr) mem[addr] <= data;
   end
+
+  // synthesis translate_off
+  initial begin
+    string src;
+    if ($value$plusargs("ram=%s", src)) $readmemh(src, mem);
+  end
+  // synthesis translate_on
 endmodule
@@ -1,6 +1,7 @@
 -Wno-fatal
 -j 0
 --binary
+--trace
 --Mdir build/verilator
 --top simulator
 
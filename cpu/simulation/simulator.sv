@@ -9,6 +9,15 @@ module simulator;
 
   initial forever #10 clk = ~clk;
 
+  // dump every signal to a value change dump with +vcd=<FILE>
+  initial begin
+    string vcd;
+    if ($value$plusargs("vcd=%s", vcd)) begin
+      $dumpfile(vcd);
+      $dumpvars(0, simulator);
+    end
+  end
+
   cpu cpu0 (.*);
 
   // 0x0000 to 0x3fff (2^14 addresses)
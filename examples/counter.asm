@@ -2,7 +2,7 @@
 prints 0 to 9 to the tty
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 
 	num = r0; // alias num to r0
 	new_line = r1; // alias new_line to r1
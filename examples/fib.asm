@@ -3,7 +3,7 @@ prints first 10 fibonacci numbers, starting at 0
 yes, I know this is incredibly inefficient
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 import print_num from "lib/print.asm";
 
 	loop_max = 10; // alias loop_map to 10
@@ -2,7 +2,7 @@
 prints a few strings
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 import print from "lib/print.asm";
 
 	// call print(string1)
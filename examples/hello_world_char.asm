@@ -2,7 +2,7 @@
 hello world program using only characters
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 
 	ld r0, 'H'; // load r0 with the ascii value of H
 	ld tty, r0; // print it
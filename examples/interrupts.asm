@@ -2,7 +2,7 @@
 sets up the interupt vector table, and initalises the timer to create an interrupt
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 import print from "lib/print.asm";
 
 	// interrupt vector table
@@ -1,4 +1,4 @@
-import * from "defines.asm";
+import * from "target";
 
 // does long division (see https://en.wikipedia.org/wiki/Division_algorithm#Integer_division_(unsigned)_with_remainder)
 // inputs: numerator, divisor
@@ -1,4 +1,4 @@
-import * from "defines.asm";
+import * from "target";
 
 // inputs: string to print
 export print: {
@@ -2,7 +2,7 @@
 multiplies two numbers and prints the result
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 import print_num from "lib/print.asm";
 import mul from "lib/num.asm";
 
@@ -2,7 +2,7 @@
 prints the prime numbers up to 100
 */
 
-import * from "lib/defines.asm";
+import * from "target";
 import print_num from "lib/print.asm";
 
 	loop_max = 100; // alias loop_max to 100
@@ -0,0 +1,4 @@
+#!/bin/bash
+
+disassembler_path="$(dirname "$BASH_SOURCE")/../assembler/target/release/ablomm_disasm"
+./$disassembler_path "$@"
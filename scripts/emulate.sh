@@ -0,0 +1,4 @@
+#!/bin/bash
+
+emulator_path="$(dirname "$BASH_SOURCE")/../assembler/target/release/ablomm_emu"
+./$emulator_path "$@"
@@ -0,0 +1,4 @@
+#!/bin/bash
+
+trace_path="$(dirname "$BASH_SOURCE")/../assembler/target/release/ablomm_trace"
+./$trace_path "$@"